@@ -0,0 +1,78 @@
+//! Record how the binary was built, for `--version`
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    // A missing file would make cargo run the script for every build, e.g. in a published crate.
+    if Path::new(".git/HEAD").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        println!("cargo:rerun-if-changed=.git/index");
+    } else {
+        println!("cargo:rerun-if-changed=build.rs");
+    }
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    let commit = output("git", &["rev-parse", "--short=12", "HEAD"]);
+    let rustc = output(
+        &env::var("RUSTC").unwrap_or_else(|_| "rustc".into()),
+        &["-V"],
+    );
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(name, _)| Some(name.strip_prefix("CARGO_FEATURE_")?.to_lowercase()))
+        .filter(|feature| feature != "default")
+        .map(|feature| feature.replace('_', "-"))
+        .collect();
+    features.sort();
+
+    println!(
+        "cargo:rustc-env=NOISY_GIT_COMMIT={}",
+        commit.as_deref().unwrap_or("unknown"),
+    );
+    println!("cargo:rustc-env=NOISY_BUILD_DATE={}", build_date());
+    println!(
+        "cargo:rustc-env=NOISY_RUSTC_VERSION={}",
+        rustc.as_deref().unwrap_or("unknown"),
+    );
+    println!("cargo:rustc-env=NOISY_FEATURES={}", features.join(", "));
+}
+
+/// The first line that `program` prints, if it could be run successfully
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    Some(stdout.lines().next()?.trim().to_owned()).filter(|line| !line.is_empty())
+}
+
+/// The UTC date of the build as "YYYY-MM-DD", `SOURCE_DATE_EPOCH` takes precedence for
+/// reproducible builds
+fn build_date() -> String {
+    let seconds = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.trim().parse().ok())
+        .or_else(|| Some(SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs()))
+        .unwrap_or_default();
+    let (year, month, day) = civil_from_days(seconds / 86_400);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// The proleptic Gregorian date of the day `days` after 1970-01-01, cf. Howard Hinnant's
+/// "chrono-Compatible Low-Level Date Algorithms"
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
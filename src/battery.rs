@@ -0,0 +1,51 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use starship_battery::{Manager, State};
+use tracing::{debug, info};
+
+use crate::{Error, Gain};
+
+/// Spawn a thread that plays the noise with `battery_amplitude` while the system runs on battery
+/// power, and with `amplitude` while it is plugged in
+pub(crate) fn spawn(gain: Arc<Gain>, amplitude: f32, battery_amplitude: f32) -> Result<(), Error> {
+    let manager = Manager::new().map_err(Error::Battery)?;
+    let _: thread::JoinHandle<()> = thread::Builder::new()
+        .name("battery".into())
+        .spawn(move || {
+            let mut last = false;
+            loop {
+                let on_battery = on_battery(&manager);
+                if on_battery != last {
+                    last = on_battery;
+                    let (power, amplitude) = if on_battery {
+                        ("battery", battery_amplitude)
+                    } else {
+                        ("AC", amplitude)
+                    };
+                    info!("Running on {power} power, changing the amplitude to {amplitude:.2}%.");
+                    gain.set(amplitude * 0.01);
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        })
+        .map_err(Error::Thread)?;
+    Ok(())
+}
+
+/// Whether any battery is discharging, systems without a battery are always plugged in
+fn on_battery(manager: &Manager) -> bool {
+    match manager.batteries() {
+        Ok(batteries) => batteries
+            .filter_map(Result::ok)
+            .any(|battery| matches!(battery.state(), State::Discharging | State::Empty)),
+        Err(err) => {
+            debug!("Could not read the battery state: {err}");
+            false
+        }
+    }
+}
+
+/// How often the power status is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
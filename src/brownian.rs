@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng};
+use rodio::source::{SeekError, noise};
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+use crate::Error;
+
+/// Brownian noise with an adjustable leak of its integrator
+///
+/// The same leaky integration of gaussian white noise as [`noise::Brownian`], but the integrator
+/// loses the fraction `leak` of its value every sample. A larger leak means less wander in the low
+/// end. The output is scaled, so it has the same level with any leak.
+#[derive(Debug, Clone)]
+pub struct LeakyBrownian<R: Rng> {
+    sample_rate: SampleRate,
+    white: noise::WhiteGaussian<R>,
+    accumulator: f32,
+    leak_factor: f32,
+    scale: f32,
+}
+
+impl<R: Rng + SeedableRng> LeakyBrownian<R> {
+    /// Generate brownian noise using the random number generator `rng`
+    ///
+    /// # Errors
+    ///
+    /// Fails if `leak` is not in the range between 0 and 1, both exclusive.
+    pub fn with_leak(sample_rate: SampleRate, leak: f32, rng: R) -> Result<Self, Error> {
+        if !(leak > 0.0 && leak < 1.0) {
+            return Err(Error::BrownianLeak(leak));
+        }
+        let white = noise::WhiteGaussian::new_with_rng(sample_rate, rng);
+        let leak_factor = 1.0 - leak;
+        let stddev = white.std_dev();
+        let variance = stddev * stddev / (1.0 - leak_factor * leak_factor);
+        Ok(Self {
+            sample_rate,
+            white,
+            accumulator: 0.0,
+            leak_factor,
+            scale: 1.0 / variance.sqrt(),
+        })
+    }
+}
+
+impl<R: Rng> Iterator for LeakyBrownian<R> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.accumulator = self.accumulator * self.leak_factor + self.white.next()?;
+        Some(self.accumulator * self.scale)
+    }
+}
+
+impl<R: Rng> Source for LeakyBrownian<R> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+use crate::Error;
+
+/// The settings of a [`Compressor`]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Settings {
+    /// The level in dBFS above which the gain is reduced
+    pub(crate) threshold_db: f32,
+    /// How many dB the input has to rise above the threshold to raise the output by one dB
+    pub(crate) ratio: f32,
+    /// How fast the gain is reduced in milliseconds
+    pub(crate) attack_ms: f32,
+    /// How fast the gain recovers in milliseconds
+    pub(crate) release_ms: f32,
+}
+
+impl Settings {
+    fn check(self) -> Result<Self, Error> {
+        if !self.threshold_db.is_finite() || self.threshold_db > 0.0 {
+            return Err(Error::Threshold(self.threshold_db));
+        }
+        if !self.ratio.is_finite() || self.ratio < 1.0 {
+            return Err(Error::Ratio(self.ratio));
+        }
+        for time in [self.attack_ms, self.release_ms] {
+            if !time.is_finite() || time <= 0.0 {
+                return Err(Error::CompressorTime(time));
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// The gain reduction in dB for an input at `level_db`, the static curve of the compressor
+pub(crate) fn gain_db(level_db: f32, threshold_db: f32, ratio: f32) -> f32 {
+    if level_db > threshold_db {
+        (threshold_db - level_db) * (1.0 - 1.0 / ratio)
+    } else {
+        0.0
+    }
+}
+
+/// A feed-forward compressor, which follows the level of every channel on its own
+///
+/// The level is the mean square of the last 50 ms, and the gain reduction follows it with the
+/// attack and release times.
+pub(crate) struct Compressor<S> {
+    input: S,
+    settings: Settings,
+    /// The smoothing factors of the level, and of the gain when it falls or rises
+    window: f32,
+    attack: f32,
+    release: f32,
+    envelopes: Vec<Envelope>,
+    channel: usize,
+}
+
+/// The state of the envelope follower of one channel
+#[derive(Debug, Clone, Copy, Default)]
+struct Envelope {
+    mean_square: f32,
+    gain_db: f32,
+}
+
+impl<S: Source> Compressor<S> {
+    pub(crate) fn new(input: S, settings: Settings) -> Result<Self, Error> {
+        let settings = settings.check()?;
+        #[expect(clippy::cast_precision_loss)]
+        let sample_rate = input.sample_rate() as f32;
+        let factor = |ms: f32| (-1000.0 / (ms * sample_rate)).exp();
+        let envelopes = vec![Envelope::default(); input.channels().into()];
+        Ok(Self {
+            input,
+            settings,
+            window: factor(WINDOW_MS),
+            attack: factor(settings.attack_ms),
+            release: factor(settings.release_ms),
+            envelopes,
+            channel: 0,
+        })
+    }
+}
+
+impl<S: Source> Iterator for Compressor<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        let Envelope {
+            mut mean_square,
+            gain_db: mut gain,
+        } = self.envelopes[self.channel];
+
+        let power = sample * sample;
+        mean_square = power + self.window * (mean_square - power);
+        let level_db = 10.0 * mean_square.max(f32::MIN_POSITIVE).log10();
+        let target = gain_db(level_db, self.settings.threshold_db, self.settings.ratio);
+        let factor = if target < gain {
+            self.attack
+        } else {
+            self.release
+        };
+        gain = target + factor * (gain - target);
+
+        self.envelopes[self.channel] = Envelope {
+            mean_square,
+            gain_db: gain,
+        };
+        self.channel += 1;
+        if self.channel == self.envelopes.len() {
+            self.channel = 0;
+        }
+        Some(sample * 10f32.powf(gain / 20.0))
+    }
+}
+
+impl<S: Source> Source for Compressor<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.channel = 0;
+        self.input.try_seek(pos)
+    }
+}
+
+/// The duration over which the level is averaged in milliseconds
+const WINDOW_MS: f32 = 50.0;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn static_curve() {
+        let (threshold_db, ratio) = (-20.0, 4.0);
+        // Below the threshold the gain stays, at the hard knee it starts to fall, above the
+        // threshold the output rises by 1 dB per 4 dB of the input.
+        for (level_db, expected) in [(-40.0, 0.0), (-20.0, 0.0), (-16.0, -3.0), (0.0, -15.0)] {
+            let actual = gain_db(level_db, threshold_db, ratio);
+            assert!((actual - expected).abs() < 1e-5, "{level_db} dB: {actual}");
+        }
+        assert!(gain_db(-10.0, threshold_db, 1.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,370 @@
+use std::ffi::OsString;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::io::{ErrorKind, Write, stdout};
+use std::path::{Path, PathBuf};
+
+use clap::parser::{ArgMatches, ValueSource};
+use clap::{Arg, ArgAction, Command, CommandFactory, FromArgMatches, ValueEnum};
+use noisy_silence::NoiseValue;
+
+use crate::{Args, Error, MAX_EQ_BANDS};
+
+/// Parse the command line arguments, and take the settings that were not given explicitly from the
+/// selected preset, or else from the config file, or else from the remembered state
+pub(crate) fn parse_args() -> Result<Args, Error> {
+    let matches = command().get_matches_from(argv()?);
+    Ok(from_matches(&matches).unwrap_or_else(|err| err.exit()))
+}
+
+/// Read the config file again, e.g. on SIGHUP, without exiting if the settings are invalid
+pub(crate) fn reload_args() -> Result<Args, Error> {
+    command()
+        .try_get_matches_from(argv()?)
+        .and_then(|matches| from_matches(&matches))
+        .map_err(Error::Reload)
+}
+
+/// The settings in `matches`, with the checks that clap cannot do itself
+fn from_matches(matches: &ArgMatches) -> Result<Args, clap::Error> {
+    let args = Args::from_arg_matches(matches)?;
+    // `--eq` can be repeated, so clap cannot limit the number of its values.
+    if args.eq.len() > MAX_EQ_BANDS {
+        return Err(command().error(
+            clap::error::ErrorKind::TooManyValues,
+            format!(
+                "too many bands for '--eq': {}, at most {MAX_EQ_BANDS} are supported",
+                args.eq.len(),
+            ),
+        ));
+    }
+    Ok(args)
+}
+
+/// The command line interface, every argument can be given in a `NOISY_*` environment variable,
+/// too, e.g. `NOISY_AMPLITUDE_DB` for `--amplitude-db`
+pub(crate) fn command() -> Command {
+    Args::command().mut_args(|arg| {
+        if arg.get_env().is_some() {
+            return arg;
+        }
+        let name = format!("NOISY_{}", arg.get_id().as_str().to_uppercase());
+        arg.env(name)
+    })
+}
+
+/// The command line arguments, with the settings of the preset and the config file added to them
+fn argv() -> Result<Vec<OsString>, Error> {
+    let command = command();
+    let cli: Vec<OsString> = std::env::args_os().collect();
+    let matches = command.clone().get_matches_from(&cli);
+
+    let config = if let Some(path) = matches.get_one::<PathBuf>("config") {
+        Some((path.clone(), true))
+    } else {
+        default_path()
+            .filter(|path| path.is_file())
+            .map(|path| (path, false))
+    };
+    let (mut table, presets) = match &config {
+        Some((path, _)) => read_config(path)?,
+        None => Default::default(),
+    };
+    apply_preset(&mut table, &presets, matches.get_one::<String>("preset"))?;
+    if matches.get_flag("remember") || table.get("remember") == Some(&toml::Value::Boolean(true)) {
+        apply_state(&command, &matches, &mut table)?;
+    }
+    if config.is_none() && table.is_empty() {
+        return Ok(cli);
+    }
+
+    let mut options = Vec::new();
+    let mut positionals = vec![None; command.get_positionals().count()];
+    for (key, value) in table {
+        let Some(arg) = find_arg(&command, &key) else {
+            return Err(Error::ConfigKey(key));
+        };
+        if is_explicit(&matches, arg) {
+            continue;
+        }
+
+        let values = match value {
+            toml::Value::Array(values) => values,
+            value => vec![value],
+        };
+        for value in values {
+            let value = match value {
+                toml::Value::Boolean(value) if matches!(arg.get_action(), ArgAction::SetTrue) => {
+                    if value {
+                        options.push(format!("--{key}"));
+                    }
+                    continue;
+                }
+                toml::Value::String(value) => value,
+                toml::Value::Integer(value) => value.to_string(),
+                toml::Value::Float(value) => value.to_string(),
+                _ => return Err(Error::ConfigValue(key)),
+            };
+            match command
+                .get_positionals()
+                .position(|p| p.get_id() == arg.get_id())
+            {
+                Some(index) => positionals[index] = Some(value),
+                None => options.push(format!("--{key}={value}")),
+            }
+        }
+    }
+
+    // Positional arguments that are given on the command line stay in place. The ones from the
+    // config file are appended, so gaps need to be filled with the values of their environment
+    // variables, or else with their default values.
+    let given = command
+        .get_positionals()
+        .take_while(|arg| {
+            let source = matches.value_source(arg.get_id().as_str());
+            matches!(source, Some(ValueSource::CommandLine))
+        })
+        .count();
+    positionals.truncate(
+        positionals
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |i| i + 1),
+    );
+    let positionals = command
+        .get_positionals()
+        .zip(positionals)
+        .skip(given)
+        .map(|(arg, value)| {
+            value
+                .or_else(|| env_value(&matches, arg))
+                .unwrap_or_else(|| {
+                    arg.get_default_values()
+                        .first()
+                        .map(|value| value.to_string_lossy().into_owned())
+                        .unwrap_or_default()
+                })
+        });
+
+    let mut argv = cli;
+    let rest = argv.split_off(1.min(argv.len()));
+    if let Some((path, false)) = config {
+        let mut config = OsString::from("--config=");
+        config.push(path);
+        argv.push(config);
+    }
+    argv.extend(options.into_iter().map(OsString::from));
+    argv.extend(rest);
+    argv.extend(positionals.map(OsString::from));
+    Ok(argv)
+}
+
+/// The argument that the setting `key` in the config file stands for
+fn find_arg<'a>(command: &'a Command, key: &str) -> Option<&'a Arg> {
+    command.get_arguments().find(|arg| match arg.get_long() {
+        Some(long) => long == key && long != "config",
+        None => arg.get_id() == key,
+    })
+}
+
+/// Whether `arg` was given on the command line, or in an environment variable
+fn is_explicit(matches: &ArgMatches, arg: &Arg) -> bool {
+    matches!(
+        matches.value_source(arg.get_id().as_str()),
+        Some(ValueSource::CommandLine | ValueSource::EnvVariable),
+    )
+}
+
+/// The value of `arg` if it was given in an environment variable
+fn env_value(matches: &ArgMatches, arg: &Arg) -> Option<String> {
+    if matches.value_source(arg.get_id().as_str()) != Some(ValueSource::EnvVariable) {
+        return None;
+    }
+    let value = matches.get_raw(arg.get_id().as_str())?.next()?;
+    Some(value.to_string_lossy().into_owned())
+}
+
+/// Add the remembered settings to `table`, unless they are already set, or conflict with a setting
+/// that is
+fn apply_state(
+    command: &Command,
+    matches: &ArgMatches,
+    table: &mut toml::Table,
+) -> Result<(), Error> {
+    let Some(path) = state_path() else {
+        return Ok(());
+    };
+    let state: toml::Table = match read_to_string(&path) {
+        Ok(state) => state
+            .parse()
+            .map_err(|err| Error::ConfigParse(path.clone(), err))?,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(Error::ConfigRead(path, err)),
+    };
+    for (key, value) in state {
+        // The state file was written by another version of the program, maybe.
+        let Some(arg) = find_arg(command, &key) else {
+            continue;
+        };
+        let is_set = |other: &Arg| {
+            is_explicit(matches, other)
+                || table.contains_key(other.get_long().unwrap_or(other.get_id().as_str()))
+        };
+        let conflicts = command.get_arguments().any(|other| {
+            (command.get_arg_conflicts_with(arg).contains(&other)
+                || command.get_arg_conflicts_with(other).contains(&arg))
+                && is_set(other)
+        });
+        if !is_set(arg) && !conflicts {
+            let _: Option<toml::Value> = table.insert(key, value);
+        }
+    }
+    Ok(())
+}
+
+/// Write the noise type and the amplitude in percent to the state file for `--remember`, and
+/// return its path
+pub(crate) fn remember(noise: NoiseValue, amplitude: f32) -> Result<PathBuf, Error> {
+    let path = state_path().ok_or(Error::StateDir)?;
+    // Round away the noise of the f32 in the file, e.g. 0.10000000149011612.
+    let amplitude = (f64::from(amplitude) * 10_000.0).round() / 10_000.0;
+    let state: toml::Table = noise
+        .to_possible_value()
+        .map(|noise| ("noise".to_owned(), noise.get_name().into()))
+        .into_iter()
+        .chain([("amplitude".to_owned(), amplitude.into())])
+        .collect();
+    if let Some(dir) = path.parent() {
+        create_dir_all(dir).map_err(|err| Error::StateWrite(path.clone(), err))?;
+    }
+    write(&path, state.to_string()).map_err(|err| Error::StateWrite(path.clone(), err))?;
+    Ok(path)
+}
+
+/// Print the settings of all presets, the ones in the config file first
+pub(crate) fn list_presets(config: Option<&Path>) -> Result<(), Error> {
+    let presets = match config {
+        Some(path) => read_config(path)?.1,
+        None => toml::Table::new(),
+    };
+    let mut out = stdout().lock();
+    for (name, preset) in presets.iter().chain(&built_in_presets()) {
+        let _: std::io::Result<()> = writeln!(out, "{name}:");
+        if let toml::Value::Table(preset) = preset {
+            for (key, value) in preset {
+                let _: std::io::Result<()> = writeln!(out, "    {key} = {value}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The settings in the config file at `path`, and the presets in its `[presets]` table
+fn read_config(path: &Path) -> Result<(toml::Table, toml::Table), Error> {
+    let mut table: toml::Table = read_to_string(path)
+        .map_err(|err| Error::ConfigRead(path.to_owned(), err))?
+        .parse()
+        .map_err(|err| Error::ConfigParse(path.to_owned(), err))?;
+    let presets = match table.remove("presets") {
+        Some(toml::Value::Table(presets)) => presets,
+        Some(_) => return Err(Error::ConfigValue("presets".into())),
+        None => toml::Table::new(),
+    };
+    Ok((table, presets))
+}
+
+/// Override the settings in `table` with the ones of the preset that was selected on the command
+/// line, or in the config file
+fn apply_preset(
+    table: &mut toml::Table,
+    presets: &toml::Table,
+    selected: Option<&String>,
+) -> Result<(), Error> {
+    let name = match (selected, table.get("preset")) {
+        (Some(name), _) | (None, Some(toml::Value::String(name))) => name.clone(),
+        (None, Some(_)) => return Err(Error::ConfigValue("preset".into())),
+        (None, None) => return Ok(()),
+    };
+    table.extend(find_preset(presets, &name)?);
+    Ok(())
+}
+
+/// The preset `name`, the presets in the config file take precedence over the built-in ones
+fn find_preset(presets: &toml::Table, name: &str) -> Result<toml::Table, Error> {
+    match presets.get(name).or(built_in_presets().get(name)) {
+        Some(toml::Value::Table(preset)) => Ok(preset.clone()),
+        Some(_) => Err(Error::ConfigValue(format!("presets.{name}"))),
+        None => Err(Error::Preset(name.to_owned())),
+    }
+}
+
+/// The presets in `presets.toml`
+fn built_in_presets() -> toml::Table {
+    // The tests make sure that the file is valid.
+    include_str!("presets.toml").parse().unwrap_or_default()
+}
+
+/// `noisy-silence/state.toml` in the user's data directory, for `--remember`
+fn state_path() -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join("noisy-silence").join("state.toml"))
+}
+
+/// Whether the settings are read from a config file, so it makes sense to reload them on SIGHUP
+pub(crate) fn has_file(args: &Args) -> bool {
+    args.config.is_some() || default_path().is_some_and(|path| path.is_file())
+}
+
+/// `noisy-silence/config.toml` in the user's config directory
+fn default_path() -> Option<PathBuf> {
+    Some(
+        dirs::config_dir()?
+            .join("noisy-silence")
+            .join("config.toml"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_presets_are_valid() {
+        let command = command();
+        let presets = built_in_presets();
+        assert!(presets.contains_key("sleep"));
+        for (name, preset) in &presets {
+            let toml::Value::Table(preset) = preset else {
+                panic!("{name} is not a table");
+            };
+            let mut options = Vec::new();
+            let mut positionals = vec![String::new(); command.get_positionals().count()];
+            for (key, value) in preset {
+                let arg = find_arg(&command, key).unwrap_or_else(|| panic!("{name}: {key}"));
+                let value = match value {
+                    toml::Value::Boolean(true) => {
+                        options.push(format!("--{key}"));
+                        continue;
+                    }
+                    toml::Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                match command
+                    .get_positionals()
+                    .position(|p| p.get_id() == arg.get_id())
+                {
+                    Some(index) => positionals[index] = value,
+                    None => options.push(format!("--{key}={value}")),
+                }
+            }
+            let positionals = positionals
+                .into_iter()
+                .take_while(|value| !value.is_empty());
+            let argv = ["noisy-silence".to_owned()]
+                .into_iter()
+                .chain(options)
+                .chain(positionals);
+            if let Err(err) = command.clone().try_get_matches_from(argv) {
+                panic!("{name}: {err}");
+            }
+        }
+    }
+}
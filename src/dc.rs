@@ -0,0 +1,92 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+/// Removes the DC offset of a mono source with a one-pole high-pass filter at 2 Hz
+///
+/// Brownian noise slowly wanders away from zero, and this keeps it centered over long sessions.
+#[derive(Debug, Clone)]
+pub struct DcBlocker<S> {
+    input: S,
+    pole: f32,
+    x1: f32,
+    y1: f32,
+}
+
+impl<S: Source> DcBlocker<S> {
+    /// Remove the DC offset of `input`
+    pub fn new(input: S) -> Self {
+        #[expect(clippy::cast_precision_loss)]
+        let pole = 1.0 - TAU * CUTOFF / input.sample_rate() as f32;
+        Self {
+            input,
+            pole,
+            x1: 0.0,
+            y1: 0.0,
+        }
+    }
+}
+
+impl<S: Source> Iterator for DcBlocker<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let x = self.input.next()?;
+        self.y1 = x - self.x1 + self.pole * self.y1;
+        self.x1 = x;
+        Some(self.y1)
+    }
+}
+
+impl<S: Source> Source for DcBlocker<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+/// The cutoff frequency in Hz, well below the lowest audible frequencies
+const CUTOFF: f32 = 2.0;
+
+#[cfg(test)]
+mod tests {
+    use rodio::source::noise::Brownian;
+
+    use super::*;
+    use crate::RngValue;
+
+    #[test]
+    fn brownian_noise_stays_centered() {
+        let brownian = Brownian::new_with_rng(48_000, RngValue::default().to_rng([0; 16]));
+        let mut source = DcBlocker::new(brownian);
+        // Ten minutes in blocks of ten seconds, the peaks of the noise are around 2.5.
+        for block in 0..60 {
+            let sum: f64 = source.by_ref().take(480_000).map(f64::from).sum();
+            let mean = sum / 480_000.0;
+            assert!(mean.abs() < 0.02, "block {block}: mean {mean}");
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::sync::mpsc;
+use std::time::Duration;
+
+use noisy_silence::NoiseRng;
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+use tracing::info;
+
+use crate::{DurationRange, Error, duration_to_samples};
+
+/// Alternates between playing a source and true silence
+pub(crate) struct DutyCycle<S> {
+    input: S,
+    on: Duration,
+    off: Duration,
+    /// The length of the on-phase in samples
+    on_samples: u64,
+    /// The length of a whole cycle in samples
+    period: u64,
+    position: u64,
+    phases: mpsc::SyncSender<Phase>,
+}
+
+impl<S: Source> DutyCycle<S> {
+    pub(crate) fn new(
+        input: S,
+        on: Duration,
+        off: Duration,
+        phases: mpsc::SyncSender<Phase>,
+    ) -> Result<Self, Error> {
+        let on_samples = duration_to_samples(&input, on);
+        let period = on_samples + duration_to_samples(&input, off);
+        // An on-phase shorter than one sample would play silence forever.
+        if on_samples == 0 || period == 0 {
+            return Err(Error::DutyCycle);
+        }
+        Ok(Self {
+            input,
+            on,
+            off,
+            on_samples,
+            period,
+            position: 0,
+            phases,
+        })
+    }
+}
+
+impl<S: Source> Iterator for DutyCycle<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        let position = self.position;
+        self.position += 1;
+        if self.position == self.period {
+            self.position = 0;
+            let _: Result<(), _> = self.phases.try_send(Phase::Playing(self.on));
+        }
+        if position < self.on_samples {
+            Some(sample)
+        } else {
+            if position == self.on_samples {
+                let _: Result<(), _> = self.phases.try_send(Phase::Pausing(self.off));
+            }
+            Some(0.0)
+        }
+    }
+}
+
+impl<S: Source> Source for DutyCycle<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.position = duration_to_samples(&self.input, pos) % self.period;
+        self.input.try_seek(pos)
+    }
+}
+
+/// A transition of a [`DutyCycle`] or an [`Intermittent`], and how long the new phase lasts
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Phase {
+    Playing(Duration),
+    Pausing(Duration),
+}
+
+/// Spawn a thread that logs the transitions of a [`DutyCycle`] or an [`Intermittent`], so the
+/// audio thread never waits for the log output
+///
+/// The thread ends when the sender is dropped together with the source.
+pub(crate) fn spawn_phase_log() -> Result<mpsc::SyncSender<Phase>, Error> {
+    let (tx, rx) = mpsc::sync_channel(4);
+    let _: std::thread::JoinHandle<()> = std::thread::Builder::new()
+        .name("phases".into())
+        .spawn(move || {
+            for phase in rx {
+                match phase {
+                    Phase::Playing(duration) => {
+                        info!(
+                            "Playing noise for {}.",
+                            humantime::format_duration(duration)
+                        );
+                    }
+                    Phase::Pausing(duration) => {
+                        info!(
+                            "Pausing noise for {}.",
+                            humantime::format_duration(duration)
+                        );
+                    }
+                }
+            }
+        })
+        .map_err(Error::Thread)?;
+    Ok(tx)
+}
+
+/// Alternates between playing a source and true silence, for random durations
+pub(crate) struct Intermittent<S> {
+    input: S,
+    on: DurationRange,
+    off: DurationRange,
+    rng: NoiseRng,
+    playing: bool,
+    /// The samples left until the next transition
+    remaining: u64,
+    phases: mpsc::SyncSender<Phase>,
+}
+
+impl<S: Source> Intermittent<S> {
+    pub(crate) fn new(
+        input: S,
+        on: DurationRange,
+        off: DurationRange,
+        mut rng: NoiseRng,
+        phases: mpsc::SyncSender<Phase>,
+    ) -> Self {
+        let duration = on.sample(&mut rng);
+        let _: Result<(), _> = phases.try_send(Phase::Playing(duration));
+        Self {
+            remaining: duration_to_samples(&input, duration).max(u64::from(input.channels())),
+            input,
+            on,
+            off,
+            rng,
+            playing: true,
+            phases,
+        }
+    }
+}
+
+impl<S: Source> Iterator for Intermittent<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        let playing = self.playing;
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            self.playing = !self.playing;
+            let (duration, phase) = if self.playing {
+                let duration = self.on.sample(&mut self.rng);
+                (duration, Phase::Playing(duration))
+            } else {
+                let duration = self.off.sample(&mut self.rng);
+                (duration, Phase::Pausing(duration))
+            };
+            let _: Result<(), _> = self.phases.try_send(phase);
+            // Stay in sync with the frames, and play at least one.
+            self.remaining =
+                duration_to_samples(&self.input, duration).max(u64::from(self.input.channels()));
+        }
+        Some(if playing { sample } else { 0.0 })
+    }
+}
+
+impl<S: Source> Source for Intermittent<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duty_cycle_needs_samples() {
+        let source = || rodio::buffer::SamplesBuffer::new(1, 48_000, vec![1.0; 16]);
+        let (tx, _) = mpsc::sync_channel(1);
+        let on = Duration::from_nanos(1);
+        assert!(DutyCycle::new(source(), on, on, tx.clone()).is_err());
+        assert!(DutyCycle::new(source(), Duration::from_millis(1), on, tx).is_ok());
+    }
+}
@@ -0,0 +1,112 @@
+use std::fs::read_to_string;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Error, Gain};
+
+/// The breakpoints of an amplitude envelope, read from a `--envelope` file
+///
+/// Each line contains the time in seconds and the amplitude in percent, separated by a comma or a
+/// tab, e.g. `90,2.5`. Empty lines and lines starting with `#` are ignored.
+#[derive(Debug, Clone)]
+pub(crate) struct Envelope {
+    /// The times in seconds are strictly increasing, the first time is not negative
+    points: Vec<(f32, f32)>,
+}
+
+impl Envelope {
+    /// Read and check the breakpoints in `path`
+    pub(crate) fn read(path: &Path) -> Result<Self, Error> {
+        let content =
+            read_to_string(path).map_err(|err| Error::EnvelopeRead(path.to_owned(), err))?;
+        let mut points: Vec<(f32, f32)> = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let previous = points.last().map(|&(time, _)| time);
+            let point = parse_line(line, previous)
+                .map_err(|err| Error::EnvelopeParse(path.to_owned(), index + 1, err))?;
+            points.push(point);
+        }
+        if points.is_empty() {
+            return Err(Error::EnvelopeEmpty(path.to_owned()));
+        }
+        Ok(Self { points })
+    }
+
+    /// The linearly interpolated amplitude in percent `time` seconds after the start, the first
+    /// and the last amplitude are held before and after the breakpoints
+    fn amplitude(&self, time: f32) -> f32 {
+        let next = self.points.partition_point(|&(t, _)| t <= time);
+        let Some(&(t1, a1)) = self.points.get(next) else {
+            return self.points.last().map_or(0.0, |&(_, amplitude)| amplitude);
+        };
+        let Some(&(t0, a0)) = next.checked_sub(1).and_then(|index| self.points.get(index)) else {
+            return a1;
+        };
+        a0 + (a1 - a0) * (time - t0) / (t1 - t0)
+    }
+}
+
+/// Parse a line `<SECONDS>,<PERCENT>`, its time must come after the `previous` one
+fn parse_line(line: &str, previous: Option<f32>) -> Result<(f32, f32), LineError> {
+    let mut fields = line.split([',', '\t']).map(str::trim);
+    let (Some(time), Some(amplitude), None) = (fields.next(), fields.next(), fields.next()) else {
+        return Err(LineError::Format);
+    };
+    let time: f32 = time.parse().map_err(|_| LineError::Format)?;
+    let amplitude: f32 = amplitude.parse().map_err(|_| LineError::Format)?;
+    if !(time.is_finite() && time >= 0.0) {
+        return Err(LineError::Time(time));
+    }
+    if let Some(previous) = previous
+        && time <= previous
+    {
+        return Err(LineError::NotMonotonic(time, previous));
+    }
+    if !(0.0..=100.0).contains(&amplitude) {
+        return Err(LineError::Amplitude(amplitude));
+    }
+    Ok((time, amplitude))
+}
+
+/// Why a line of an envelope file is invalid
+#[derive(Debug, Clone, Copy, thiserror::Error, displaydoc::Display)]
+pub(crate) enum LineError {
+    /// Expected a time in seconds and an amplitude in percent, e.g. "90,2.5"
+    Format,
+    /// The time {0:?} s is not zero or a positive number
+    Time(f32),
+    /// The time {0:?} s does not come after the previous breakpoint at {1:?} s
+    NotMonotonic(f32, f32),
+    /// The amplitude {0:?} is not in the range between 0% and 100%
+    Amplitude(f32),
+}
+
+/// Spawn a thread that lets the amplitude follow `envelope`, the time starts at `start`
+///
+/// The thread ends after the last breakpoint, and the amplitude stays at its value.
+pub(crate) fn spawn(envelope: Envelope, gain: Arc<Gain>, start: Instant) -> Result<(), Error> {
+    let _: thread::JoinHandle<()> = thread::Builder::new()
+        .name("envelope".into())
+        .spawn(move || {
+            let end = envelope.points.last().map_or(0.0, |&(time, _)| time);
+            loop {
+                let time = start.elapsed().as_secs_f32();
+                gain.set(envelope.amplitude(time) * 0.01);
+                if time >= end {
+                    break;
+                }
+                thread::sleep(UPDATE_INTERVAL);
+            }
+        })
+        .map_err(Error::Thread)?;
+    Ok(())
+}
+
+/// How often the amplitude is updated
+const UPDATE_INTERVAL: Duration = Duration::from_millis(20);
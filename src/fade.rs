@@ -0,0 +1,264 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::{Arc, mpsc};
+use std::time::Duration;
+
+use rodio::source::{SeekError, TakeDuration};
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+use crate::{Event, FadeCurve, duration_to_samples};
+
+/// Raises the amplitude of a source from silence to its full amplitude, for the fade-in and the
+/// `--ramp-up`
+pub(crate) struct RampUp<S> {
+    input: S,
+    curve: FadeCurve,
+    /// The length of the ramp in samples
+    length: u64,
+    position: u64,
+}
+
+impl<S: Source> RampUp<S> {
+    pub(crate) fn new(input: S, duration: Duration, curve: FadeCurve) -> Self {
+        let length = duration_to_samples(&input, duration);
+        Self {
+            input,
+            curve,
+            length,
+            position: 0,
+        }
+    }
+}
+
+impl<S: Source> Iterator for RampUp<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        if self.position >= self.length {
+            return Some(sample);
+        }
+        #[expect(clippy::cast_precision_loss)]
+        let progress = self.position as f32 / self.length as f32;
+        self.position += 1;
+        Some(sample * self.curve.gain(progress).min(1.0))
+    }
+}
+
+impl<S: Source> Source for RampUp<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.position = duration_to_samples(&self.input, pos);
+        self.input.try_seek(pos)
+    }
+}
+
+/// Plays a source for a limited time, and reports when its time is up
+pub(crate) struct Timed<S> {
+    input: TakeDuration<S>,
+    duration: Duration,
+    tx: Option<mpsc::SyncSender<Event>>,
+}
+
+impl<S: Source> Timed<S> {
+    pub(crate) fn new(input: S, duration: Duration, tx: mpsc::SyncSender<Event>) -> Self {
+        Self {
+            input: input.take_duration(duration),
+            duration,
+            tx: Some(tx),
+        }
+    }
+}
+
+impl<S: Source> Iterator for Timed<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next();
+        if sample.is_none()
+            && let Some(tx) = self.tx.take()
+        {
+            // Never block the audio thread. If the channel is full, then main is already woken up.
+            let _: Result<(), mpsc::TrySendError<Event>> = tx.try_send(Event::Elapsed);
+        }
+        sample
+    }
+}
+
+impl<S: Source> Source for Timed<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        Some(self.duration)
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+/// Fades out a source on request, or before the end of a source with a known duration
+pub(crate) struct FadeOut<S> {
+    input: S,
+    /// The length of the fade in samples
+    length: u64,
+    curve: FadeCurve,
+    /// Samples until the fade-out starts automatically
+    start: Option<u64>,
+    /// Remaining samples of an ongoing fade-out
+    remaining: Option<u64>,
+    /// Set by the main thread to request a fade-out
+    fading: Arc<AtomicBool>,
+    tx: Option<mpsc::SyncSender<Event>>,
+}
+
+impl<S: Source> FadeOut<S> {
+    pub(crate) fn new(
+        input: S,
+        duration: Duration,
+        curve: FadeCurve,
+        fading: Arc<AtomicBool>,
+        tx: mpsc::SyncSender<Event>,
+    ) -> Self {
+        let length = duration_to_samples(&input, duration);
+        let start = input
+            .total_duration()
+            .map(|total| duration_to_samples(&input, total).saturating_sub(length));
+        Self {
+            input,
+            length,
+            curve,
+            start,
+            remaining: None,
+            fading,
+            tx: Some(tx),
+        }
+    }
+}
+
+impl<S: Source> Iterator for FadeOut<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_none() {
+            let start = match &mut self.start {
+                Some(0) => true,
+                Some(start) => {
+                    *start -= 1;
+                    false
+                }
+                None => false,
+            };
+            if start || self.fading.load(Relaxed) {
+                self.remaining = Some(self.length);
+            }
+        }
+
+        let sample = self.input.next()?;
+        match &mut self.remaining {
+            None => Some(sample),
+            Some(0) if self.fading.load(Relaxed) => {
+                if let Some(tx) = self.tx.take() {
+                    // Never block the audio thread. If the channel is full, main is already woken up.
+                    let _: Result<(), mpsc::TrySendError<Event>> = tx.try_send(Event::FadedOut);
+                }
+                None
+            }
+            // An automatic fade-out ends in silence, until the input source ends.
+            Some(0) => Some(0.0),
+            #[expect(clippy::cast_precision_loss)]
+            Some(remaining) => {
+                let gain = self.curve.gain(*remaining as f32 / self.length as f32);
+                *remaining -= 1;
+                Some(sample * gain)
+            }
+        }
+    }
+}
+
+impl<S: Source> Source for FadeOut<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::ValueEnum;
+
+    use super::*;
+    use crate::MIN_FADE_IN;
+
+    #[test]
+    fn soft_start_rises_monotonically() {
+        for &curve in FadeCurve::value_variants() {
+            let ones = rodio::buffer::SamplesBuffer::new(1, 48_000, vec![1.0; 24_000]);
+            let output: Vec<Sample> = RampUp::new(ones, MIN_FADE_IN, curve).collect();
+            let (ramp, rest) = output.split_at(12_000);
+            assert!(ramp[0] < 0.01, "{curve:?} starts at {}", ramp[0]);
+            for (index, pair) in ramp.windows(2).enumerate() {
+                assert!(pair[0] <= pair[1], "{curve:?} falls at sample {index}");
+            }
+            assert!(rest.iter().all(|&sample| sample == 1.0), "{curve:?}");
+        }
+    }
+}
@@ -0,0 +1,444 @@
+use std::f32::consts::{FRAC_1_SQRT_2, PI, TAU};
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+/// The normalized coefficients of a biquad filter, cf. the "Audio EQ Cookbook" by Robert
+/// Bristow-Johnson
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Coefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Coefficients {
+    /// A second order Butterworth low-pass filter
+    pub(crate) fn low_pass(sample_rate: SampleRate, cutoff: f32) -> Self {
+        Self::resonant_low_pass(sample_rate, cutoff, FRAC_1_SQRT_2)
+    }
+
+    /// The four sections of an eighth order Butterworth low-pass filter, which falls off by 48 dB
+    /// per octave above the `cutoff`
+    pub(crate) fn band_limit(sample_rate: SampleRate, cutoff: f32) -> [Self; 4] {
+        // The poles of the sections are spread evenly over the left quarter circles.
+        std::array::from_fn(|section| {
+            #[expect(clippy::cast_precision_loss)]
+            let angle = (2 * section + 1) as f32 * PI / 16.0;
+            Self::resonant_low_pass(sample_rate, cutoff, 0.5 / angle.cos())
+        })
+    }
+
+    fn resonant_low_pass(sample_rate: SampleRate, cutoff: f32, q: f32) -> Self {
+        let (cos, alpha) = prepare(sample_rate, cutoff, q);
+        let b1 = 1.0 - cos;
+        Self::normalize(b1 / 2.0, b1, b1 / 2.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    /// A second order Butterworth high-pass filter
+    pub(crate) fn high_pass(sample_rate: SampleRate, cutoff: f32) -> Self {
+        let (cos, alpha) = prepare(sample_rate, cutoff, FRAC_1_SQRT_2);
+        let b1 = -1.0 - cos;
+        Self::normalize(
+            -b1 / 2.0,
+            b1,
+            -b1 / 2.0,
+            1.0 + alpha,
+            -2.0 * cos,
+            1.0 - alpha,
+        )
+    }
+
+    /// A band-pass filter with a peak gain of 0 dB at `center`
+    pub(crate) fn band_pass(sample_rate: SampleRate, center: f32, q: f32) -> Self {
+        let (cos, alpha) = prepare(sample_rate, center, q);
+        Self::normalize(alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    /// A band-reject filter with its notch at `center`
+    pub(crate) fn notch(sample_rate: SampleRate, center: f32, q: f32) -> Self {
+        let (cos, alpha) = prepare(sample_rate, center, q);
+        let b1 = -2.0 * cos;
+        Self::normalize(1.0, b1, 1.0, 1.0 + alpha, b1, 1.0 - alpha)
+    }
+
+    /// A peaking filter that raises the frequencies around `center` by `gain_db`, a negative gain
+    /// cuts them instead
+    pub(crate) fn peaking(sample_rate: SampleRate, center: f32, gain_db: f32, q: f32) -> Self {
+        let (cos, alpha) = prepare(sample_rate, center, q);
+        let a = 10f32.powf(gain_db / 40.0);
+        let b1 = -2.0 * cos;
+        Self::normalize(
+            1.0 + alpha * a,
+            b1,
+            1.0 - alpha * a,
+            1.0 + alpha / a,
+            b1,
+            1.0 - alpha / a,
+        )
+    }
+
+    /// The three sections of an A-weighting filter according to IEC 61672, with a gain of 0 dB at
+    /// 1 kHz
+    ///
+    /// The analog poles are prewarped before the bilinear transform, so the corner frequencies stay
+    /// in place even close to the Nyquist frequency. Returns `None` if the highest pole is not below
+    /// the Nyquist frequency, i.e. at sample rates below about 24.4 kHz, where the filter would be
+    /// unstable.
+    pub(crate) fn a_weighting(sample_rate: SampleRate) -> Option<[Self; 3]> {
+        let fs = f64::from(sample_rate);
+        if A_WEIGHTING_POLES[3] >= fs / 2.0 {
+            return None;
+        }
+        let omega = |frequency: f64| 2.0 * fs * (std::f64::consts::PI * frequency / fs).tan();
+        let [w1, w2, w3, w4] = A_WEIGHTING_POLES.map(omega);
+        let mut sections = [
+            bilinear(fs, [1.0, 0.0, 0.0], [1.0, 2.0 * w1, w1 * w1]),
+            bilinear(fs, [1.0, 0.0, 0.0], [1.0, w2 + w3, w2 * w3]),
+            bilinear(fs, [0.0, 0.0, w4 * w4], [1.0, 2.0 * w4, w4 * w4]),
+        ];
+        let gain: f64 = sections
+            .iter()
+            .map(|section| section.magnitude(sample_rate, 1000.0))
+            .product();
+        #[expect(clippy::cast_possible_truncation)]
+        let gain = (1.0 / gain) as f32;
+        sections[0].b0 *= gain;
+        sections[0].b1 *= gain;
+        sections[0].b2 *= gain;
+        Some(sections)
+    }
+
+    /// The magnitude of the response at `frequency`
+    fn magnitude(&self, sample_rate: SampleRate, frequency: f64) -> f64 {
+        let omega = std::f64::consts::TAU * frequency / f64::from(sample_rate);
+        let (sin, cos) = omega.sin_cos();
+        let (sin2, cos2) = (2.0 * omega).sin_cos();
+        let [b0, b1, b2, a1, a2] = [self.b0, self.b1, self.b2, self.a1, self.a2].map(f64::from);
+        let num = (b0 + b1 * cos + b2 * cos2).powi(2) + (b1 * sin + b2 * sin2).powi(2);
+        let den = (1.0 + a1 * cos + a2 * cos2).powi(2) + (a1 * sin + a2 * sin2).powi(2);
+        (num / den).sqrt()
+    }
+
+    fn normalize(b0: f32, b1: f32, b2: f32, a0: f32, a1: f32, a2: f32) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+/// The frequencies in Hz of the poles of the A-weighting curve, the first and the last one are
+/// double poles
+const A_WEIGHTING_POLES: [f64; 4] = [20.598_997, 107.652_65, 737.862_23, 12_194.217];
+
+/// The bilinear transform of the analog filter `(n₂s² + n₁s + n₀) / (d₂s² + d₁s + d₀)`
+#[expect(clippy::cast_possible_truncation)]
+fn bilinear(sample_rate: f64, num: [f64; 3], den: [f64; 3]) -> Coefficients {
+    let k = 2.0 * sample_rate;
+    let transform = |[c2, c1, c0]: [f64; 3]| {
+        [
+            c2 * k * k + c1 * k + c0,
+            2.0 * (c0 - c2 * k * k),
+            c2 * k * k - c1 * k + c0,
+        ]
+    };
+    let ([b0, b1, b2], [a0, a1, a2]) = (transform(num), transform(den));
+    Coefficients {
+        b0: (b0 / a0) as f32,
+        b1: (b1 / a0) as f32,
+        b2: (b2 / a0) as f32,
+        a1: (a1 / a0) as f32,
+        a2: (a2 / a0) as f32,
+    }
+}
+
+/// Returns `cos(ω)` and `α` for the center or cutoff frequency `frequency`
+fn prepare(sample_rate: SampleRate, frequency: f32, q: f32) -> (f32, f32) {
+    #[expect(clippy::cast_precision_loss)]
+    let omega = TAU * frequency / sample_rate as f32;
+    let (sin, cos) = omega.sin_cos();
+    (cos, sin / (2.0 * q))
+}
+
+/// The state of a biquad filter in the transposed direct form II
+#[derive(Debug, Clone, Copy, Default)]
+struct State {
+    z1: f32,
+    z2: f32,
+}
+
+impl State {
+    #[inline]
+    fn process(&mut self, c: &Coefficients, x: f32) -> f32 {
+        let y = c.b0 * x + self.z1;
+        self.z1 = c.b1 * x - c.a1 * y + self.z2;
+        self.z2 = c.b2 * x - c.a2 * y;
+        y
+    }
+}
+
+/// The coefficients of a running filter, which can be replaced from another thread
+///
+/// Several [`Biquad`]s can share the coefficients, e.g. one per device with `--all-devices`, each
+/// of them remembers which generation of the coefficients it uses.
+#[derive(Debug)]
+pub(crate) struct Tunable {
+    coefficients: Mutex<Coefficients>,
+    generation: AtomicU64,
+}
+
+impl Tunable {
+    pub(crate) fn new(coefficients: Coefficients) -> Self {
+        Self {
+            coefficients: Mutex::new(coefficients),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn set(&self, coefficients: Coefficients) {
+        let mut guard = self
+            .coefficients
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *guard = coefficients;
+        // Count while holding the lock, so the generation always matches the coefficients.
+        let _: u64 = self.generation.fetch_add(1, Relaxed);
+    }
+
+    /// The new coefficients if they were replaced since `generation`, which is updated, the audio
+    /// thread never waits for the lock
+    #[inline]
+    fn poll(&self, generation: &mut u64) -> Option<Coefficients> {
+        if self.generation.load(Relaxed) == *generation {
+            return None;
+        }
+        // If the lock is taken, try again with the next frame.
+        let coefficients = self.coefficients.try_lock().ok()?;
+        *generation = self.generation.load(Relaxed);
+        Some(*coefficients)
+    }
+
+    fn get(&self) -> (Coefficients, u64) {
+        let coefficients = self
+            .coefficients
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        (*coefficients, self.generation.load(Relaxed))
+    }
+}
+
+/// Applies a biquad filter to every channel of a source
+pub(crate) struct Biquad<S> {
+    input: S,
+    tunable: Arc<Tunable>,
+    coefficients: Coefficients,
+    generation: u64,
+    states: Vec<State>,
+    channel: usize,
+}
+
+impl<S: Source> Biquad<S> {
+    pub(crate) fn new(input: S, tunable: Arc<Tunable>) -> Self {
+        let states = vec![State::default(); input.channels().into()];
+        let (coefficients, generation) = tunable.get();
+        Self {
+            input,
+            tunable,
+            coefficients,
+            generation,
+            states,
+            channel: 0,
+        }
+    }
+}
+
+impl<S: Source> Iterator for Biquad<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        // Only switch to new coefficients between two frames.
+        if self.channel == 0
+            && let Some(coefficients) = self.tunable.poll(&mut self.generation)
+        {
+            self.coefficients = coefficients;
+        }
+        let sample = self.states[self.channel].process(&self.coefficients, sample);
+        self.channel += 1;
+        if self.channel == self.states.len() {
+            self.channel = 0;
+        }
+        Some(sample)
+    }
+}
+
+impl<S: Source> Source for Biquad<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.channel = 0;
+        self.states.fill(State::default());
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use noisy_silence::NoiseValue;
+
+    use super::*;
+    use crate::spectrum::Spectrum;
+
+    /// The response in dB of the cascaded `sections` at `frequency`
+    fn response(sections: &[Coefficients], sample_rate: SampleRate, frequency: f64) -> f64 {
+        sections
+            .iter()
+            .map(|c| 20.0 * c.magnitude(sample_rate, frequency).log10())
+            .sum()
+    }
+
+    /// The level in dB of a full scale sine at `frequency` after playing it through a [`Biquad`]
+    fn sine_level(coefficients: Coefficients, frequency: f32) -> f64 {
+        let sine = rodio::source::SineWave::new(frequency);
+        let filtered = Biquad::new(sine, Arc::new(Tunable::new(coefficients)));
+        // Skip the transient of the filter, and measure a whole second.
+        let sum: f64 = filtered
+            .skip(4800)
+            .take(48_000)
+            .map(|sample| f64::from(sample * sample))
+            .sum();
+        20.0 * (sum / 48_000.0 * 2.0).sqrt().log10()
+    }
+
+    #[test]
+    fn a_weighting_matches_iec_61672() {
+        let sections = Coefficients::a_weighting(48_000).unwrap();
+        assert!(response(&sections, 48_000, 1000.0).abs() < 0.01);
+        // The bilinear transform bends the upper end of the curve, but it stays well within the
+        // tolerances of a class 1 sound level meter.
+        for (frequency, expected, tolerance) in [
+            (31.5, -39.4, 0.2),
+            (63.0, -26.2, 0.2),
+            (125.0, -16.1, 0.2),
+            (250.0, -8.6, 0.2),
+            (500.0, -3.2, 0.2),
+            (1000.0, 0.0, 0.2),
+            (2000.0, 1.2, 0.2),
+            (4000.0, 1.0, 0.5),
+            (8000.0, -1.1, 1.0),
+        ] {
+            let actual = response(&sections, 48_000, frequency);
+            assert!(
+                (actual - expected).abs() < tolerance,
+                "{frequency} Hz: {actual:.2} dB instead of {expected} dB",
+            );
+        }
+    }
+
+    #[test]
+    fn band_pass_peaks_at_center() {
+        let (center, q) = (1000.0, 2.0);
+        let filter = [Coefficients::band_pass(48_000, center, q)];
+        assert!(response(&filter, 48_000, center.into()).abs() < 0.01);
+        // The response falls by 3 dB at the edges of the band, whose width is `center / q`.
+        let half = (1.0 + 1.0 / (4.0 * q * q)).sqrt();
+        for edge in [half - 0.5 / q, half + 0.5 / q] {
+            let actual = response(&filter, 48_000, f64::from(center * edge));
+            assert!((actual + 3.01).abs() < 0.1, "{actual:.2} dB at the edge");
+        }
+        for frequency in [250.0, 4000.0] {
+            assert!(response(&filter, 48_000, frequency) < -15.0);
+        }
+    }
+
+    #[test]
+    fn notch_rejects_center() {
+        let filter = Coefficients::notch(48_000, 1000.0, 5.0);
+        assert!(sine_level(filter, 1000.0) < -20.0);
+        // A sine an octave away passes.
+        for frequency in [500.0, 2000.0] {
+            let actual = sine_level(filter, frequency);
+            assert!(actual.abs() < 0.5, "{frequency} Hz: {actual:.2} dB");
+        }
+    }
+
+    #[test]
+    fn peaking_raises_and_cuts_center() {
+        for gain_db in [-12.0, 6.0] {
+            let filter = [Coefficients::peaking(48_000, 1000.0, gain_db, 1.0)];
+            let actual = response(&filter, 48_000, 1000.0);
+            assert!((actual - f64::from(gain_db)).abs() < 0.01, "{actual:.2} dB");
+            for frequency in [31.25, 16000.0] {
+                assert!(response(&filter, 48_000, frequency).abs() < 0.5);
+            }
+        }
+    }
+
+    #[test]
+    fn band_limit_removes_high_frequencies() {
+        let mut source: Box<dyn Source + Send> =
+            Box::new(noisy_silence::source(NoiseValue::White, 48_000, [0; 16], 50.0).unwrap());
+        for coefficients in Coefficients::band_limit(48_000, 2000.0) {
+            source = Box::new(Biquad::new(source, Arc::new(Tunable::new(coefficients))));
+        }
+        let spectrum = Spectrum::measure(source, 48_000);
+        let level = |center| {
+            let (_, power) = spectrum.bands.iter().find(|&&(c, _)| c == center).unwrap();
+            *power
+        };
+        // Over an octave above the cutoff, the filter has fallen by more than 48 dB.
+        for center in [8000.0, 16000.0] {
+            let below = level(1000.0) - level(center);
+            assert!(below > 60.0, "{center} Hz: only {below:.1} dB below 1 kHz");
+        }
+    }
+
+    #[test]
+    fn tunable_updates_every_reader() {
+        let tunable = Tunable::new(Coefficients::low_pass(48_000, 1000.0));
+        let (_, mut first) = tunable.get();
+        let mut second = first;
+        tunable.set(Coefficients::low_pass(48_000, 2000.0));
+        assert!(tunable.poll(&mut first).is_some());
+        assert!(tunable.poll(&mut first).is_none());
+        assert!(tunable.poll(&mut second).is_some());
+        assert!(tunable.poll(&mut second).is_none());
+    }
+
+    #[test]
+    fn a_weighting_rejects_low_sample_rates() {
+        assert!(Coefficients::a_weighting(16_000).is_none());
+        assert!(Coefficients::a_weighting(24_000).is_none());
+        assert!(Coefficients::a_weighting(44_100).is_some());
+    }
+}
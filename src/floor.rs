@@ -0,0 +1,137 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::Relaxed;
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+/// Stores the last sample of a source for a [`Floor`] further down the chain
+pub(crate) struct Tap<S> {
+    input: S,
+    last: Arc<AtomicU32>,
+}
+
+impl<S: Source> Tap<S> {
+    pub(crate) fn new(input: S, last: Arc<AtomicU32>) -> Self {
+        Self { input, last }
+    }
+}
+
+impl<S: Source> Iterator for Tap<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        self.last.store(sample.to_bits(), Relaxed);
+        Some(sample)
+    }
+}
+
+impl<S: Source> Source for Tap<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+/// Keeps the level of a source at or above `--floor`, even while the LFO, the ramp-up, a fade-in,
+/// or the off-phase of a duty cycle turn it down
+///
+/// Every stage between the [`Tap`] and this adapter passes one sample for each sample it reads,
+/// so the tapped sample belongs to the current one. Where the smoothed gain between the two falls
+/// below the floor, the missing part is mixed in from the tapped samples.
+pub(crate) struct Floor<S> {
+    input: S,
+    floor: f32,
+    dry: Arc<AtomicU32>,
+    /// The smoothed absolute values of the samples and of the tapped samples
+    level: f32,
+    dry_level: f32,
+    /// The coefficient of the one-pole smoothing
+    smoothing: f32,
+}
+
+impl<S: Source> Floor<S> {
+    /// The `floor` is given in percent, like the amplitude
+    pub(crate) fn new(input: S, floor: f32, dry: Arc<AtomicU32>) -> Self {
+        #[expect(clippy::cast_precision_loss)]
+        let rate = input.sample_rate() as f32 * f32::from(input.channels());
+        Self {
+            input,
+            floor: floor * 0.01,
+            dry,
+            level: 0.0,
+            dry_level: 0.0,
+            smoothing: 1.0 - (-1.0 / (FLOOR_SMOOTHING * rate)).exp(),
+        }
+    }
+}
+
+impl<S: Source> Iterator for Floor<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        let dry = f32::from_bits(self.dry.load(Relaxed));
+        self.level += self.smoothing * (sample.abs() - self.level);
+        self.dry_level += self.smoothing * (dry.abs() - self.dry_level);
+        if self.dry_level <= f32::EPSILON {
+            return Some(sample);
+        }
+        let gain = self.level / self.dry_level;
+        Some(sample + dry * (self.floor - gain).max(0.0))
+    }
+}
+
+impl<S: Source> Source for Floor<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+/// The time constant in seconds of the levels that a [`Floor`] compares
+const FLOOR_SMOOTHING: f32 = 0.05;
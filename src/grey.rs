@@ -0,0 +1,183 @@
+use std::f64::consts::{PI, TAU};
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng};
+use rodio::source::{SeekError, noise};
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+/// Grey noise, white noise that sounds about equally loud at every frequency
+///
+/// The white noise is shaped by a fixed EQ that approximates the inverse of the 40 phon equal
+/// loudness contour of ISO 226: two low shelves lift the bass by up to 20 dB, a dip takes away the
+/// most sensitive range around 3.5 kHz, and a high shelf lifts the treble above 8 kHz. The output
+/// has the same RMS level as the white noise it was generated from.
+#[derive(Debug)]
+pub struct GreyNoise<R: Rng> {
+    white: noise::WhiteUniform<R>,
+    sections: [Section; 4],
+    gain: f32,
+}
+
+impl<R: Rng + SeedableRng> GreyNoise<R> {
+    /// Generate grey noise using the random number generator `rng`
+    pub fn new_with_rng(sample_rate: SampleRate, rng: R) -> Self {
+        let sections = [
+            Section::low_shelf(sample_rate, 100.0, 12.0),
+            Section::low_shelf(sample_rate, 400.0, 8.0),
+            Section::peaking(sample_rate, 3500.0, -8.0, 1.4),
+            Section::high_shelf(sample_rate, 8000.0, 10.0),
+        ];
+        #[expect(clippy::cast_possible_truncation)]
+        let gain = (1.0 / rms_gain(&sections)) as f32;
+        Self {
+            white: noise::WhiteUniform::new_with_rng(sample_rate, rng),
+            sections,
+            gain,
+        }
+    }
+}
+
+impl<R: Rng> Iterator for GreyNoise<R> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.white.next()?;
+        let sample = self
+            .sections
+            .iter_mut()
+            .fold(sample, |sample, section| section.process(sample));
+        Some(sample * self.gain)
+    }
+}
+
+impl<R: Rng> Source for GreyNoise<R> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.white.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Ok(())
+    }
+}
+
+/// A biquad filter in the transposed direct form II, cf. the "Audio EQ Cookbook" by Robert
+/// Bristow-Johnson
+#[derive(Debug, Clone, Copy)]
+struct Section {
+    b: [f32; 3],
+    a: [f32; 2],
+    z: [f32; 2],
+}
+
+impl Section {
+    /// Raise the frequencies below `frequency` by `db`
+    fn low_shelf(sample_rate: SampleRate, frequency: f64, db: f64) -> Self {
+        let (a, cos, alpha) = prepare(sample_rate, frequency, db, 0.5f64.sqrt());
+        let beta = 2.0 * a.sqrt() * alpha;
+        Self::normalize(
+            [
+                a * ((a + 1.0) - (a - 1.0) * cos + beta),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - beta),
+            ],
+            [
+                (a + 1.0) + (a - 1.0) * cos + beta,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - beta,
+            ],
+        )
+    }
+
+    /// Raise the frequencies above `frequency` by `db`
+    fn high_shelf(sample_rate: SampleRate, frequency: f64, db: f64) -> Self {
+        let (a, cos, alpha) = prepare(sample_rate, frequency, db, 0.5f64.sqrt());
+        let beta = 2.0 * a.sqrt() * alpha;
+        Self::normalize(
+            [
+                a * ((a + 1.0) + (a - 1.0) * cos + beta),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - beta),
+            ],
+            [
+                (a + 1.0) - (a - 1.0) * cos + beta,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - beta,
+            ],
+        )
+    }
+
+    /// Raise the frequencies around `frequency` by `db`
+    fn peaking(sample_rate: SampleRate, frequency: f64, db: f64, q: f64) -> Self {
+        let (a, cos, alpha) = prepare(sample_rate, frequency, db, q);
+        Self::normalize([1.0 + alpha * a, -2.0 * cos, 1.0 - alpha * a], [
+            1.0 + alpha / a,
+            -2.0 * cos,
+            1.0 - alpha / a,
+        ])
+    }
+
+    #[expect(clippy::cast_possible_truncation)]
+    fn normalize(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self {
+            b: b.map(|b| (b / a[0]) as f32),
+            a: [(a[1] / a[0]) as f32, (a[2] / a[0]) as f32],
+            z: [0.0; 2],
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+
+    /// The squared magnitude of the response at the normalized angular frequency `omega`
+    fn power(&self, omega: f64) -> f64 {
+        let (sin, cos) = omega.sin_cos();
+        let (sin2, cos2) = (2.0 * omega).sin_cos();
+        let [b0, b1, b2] = self.b.map(f64::from);
+        let [a1, a2] = self.a.map(f64::from);
+        let num = (b0 + b1 * cos + b2 * cos2).powi(2) + (b1 * sin + b2 * sin2).powi(2);
+        let den = (1.0 + a1 * cos + a2 * cos2).powi(2) + (a1 * sin + a2 * sin2).powi(2);
+        num / den
+    }
+}
+
+/// Returns `A`, `cos(ω)` and `α` of a filter with a gain of `db` at `frequency`
+fn prepare(sample_rate: SampleRate, frequency: f64, db: f64, q: f64) -> (f64, f64, f64) {
+    let omega = TAU * frequency / f64::from(sample_rate);
+    let (sin, cos) = omega.sin_cos();
+    (10f64.powf(db / 40.0), cos, sin / (2.0 * q))
+}
+
+/// The factor by which the EQ changes the RMS level of white noise
+fn rms_gain(sections: &[Section]) -> f64 {
+    const STEPS: u32 = 1 << 12;
+    let total: f64 = (0..STEPS)
+        .map(|step| {
+            let omega = PI * (f64::from(step) + 0.5) / f64::from(STEPS);
+            sections.iter().map(|s| s.power(omega)).product::<f64>()
+        })
+        .sum();
+    (total / f64::from(STEPS)).sqrt()
+}
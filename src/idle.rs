@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use tracing::{debug, info};
+
+use crate::{Error, Gain};
+
+/// Spawn a thread that pauses the noise while the user did not touch the keyboard or the mouse for
+/// `after`, and resumes it on the next activity
+pub(crate) fn spawn(gain: Arc<Gain>, after: Duration) -> Result<(), Error> {
+    // Fail early, instead of never pausing the noise.
+    if idle_time().is_none() {
+        return Err(Error::IdleTime);
+    }
+    let _: thread::JoinHandle<()> = thread::Builder::new()
+        .name("idle".into())
+        .spawn(move || {
+            let mut paused = false;
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                let Some(idle) = idle_time() else {
+                    debug!("Could not read the idle time of the system.");
+                    continue;
+                };
+                if (idle >= after) != paused {
+                    paused = !paused;
+                    if paused {
+                        info!(
+                            "No activity for {}, pausing the noise.",
+                            humantime::format_duration(after),
+                        );
+                    } else {
+                        info!("Activity detected, resuming the noise.");
+                    }
+                    gain.pause(paused);
+                }
+            }
+        })
+        .map_err(Error::Thread)?;
+    Ok(())
+}
+
+/// How long the session has been idle, according to systemd-logind
+///
+/// The desktop environment sets the idle hint, e.g. GNOME after the delay of its screen blanking.
+#[cfg(target_os = "linux")]
+fn idle_time() -> Option<Duration> {
+    let session = std::env::var("XDG_SESSION_ID").unwrap_or_else(|_| "auto".into());
+    let output = run(
+        "loginctl",
+        &[
+            "show-session",
+            &session,
+            "--property=IdleHint",
+            "--property=IdleSinceHint",
+        ],
+    )?;
+    let (mut idle, mut since) = (None, None);
+    for line in output.lines() {
+        match line.split_once('=') {
+            Some(("IdleHint", value)) => idle = Some(value == "yes"),
+            Some(("IdleSinceHint", value)) => since = value.parse().ok(),
+            _ => {}
+        }
+    }
+    if !idle? {
+        return Some(Duration::ZERO);
+    }
+    let since = std::time::UNIX_EPOCH + Duration::from_micros(since?);
+    Some(since.elapsed().unwrap_or_default())
+}
+
+/// How long no input event reached the HID system
+#[cfg(target_os = "macos")]
+fn idle_time() -> Option<Duration> {
+    let output = run(
+        "ioreg",
+        &["-c", "IOHIDSystem", "-d", "4", "-r", "-k", "HIDIdleTime"],
+    )?;
+    let nanos = output.lines().find_map(|line| {
+        let (_, value) = line.split_once("\"HIDIdleTime\" = ")?;
+        value.trim().parse().ok()
+    })?;
+    Some(Duration::from_nanos(nanos))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn idle_time() -> Option<Duration> {
+    None
+}
+
+/// The stdout of `program`, if it could be run successfully
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+/// How often the idle time is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
@@ -0,0 +1,75 @@
+use std::sync::{Arc, mpsc};
+use std::{io, thread};
+
+use crossterm::event::{self, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+
+use crate::{CtrlcMode, Error, Event, Gain, cancel};
+
+/// Puts the terminal into raw mode to read single keypresses, and restores it when dropped
+#[derive(Debug)]
+pub(crate) struct RawMode(());
+
+impl RawMode {
+    pub(crate) fn enable() -> Result<Self, Error> {
+        terminal::enable_raw_mode().map_err(Error::Terminal)?;
+        Ok(Self(()))
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+/// Leave raw mode, if the terminal is in raw mode
+pub(crate) fn restore_terminal() {
+    let _: io::Result<()> = terminal::disable_raw_mode();
+}
+
+/// Change `gain` according to the user's keypresses in a background thread
+pub(crate) fn spawn(
+    gain: Arc<Gain>,
+    amplitude: f32,
+    ctrlc_mode: CtrlcMode,
+    tx: mpsc::SyncSender<Event>,
+) -> Result<(), Error> {
+    let _: thread::JoinHandle<()> = thread::Builder::new()
+        .name("keyboard".into())
+        .spawn(move || run(&gain, amplitude, ctrlc_mode, &tx))
+        .map_err(Error::Terminal)?;
+    Ok(())
+}
+
+fn run(gain: &Gain, mut amplitude: f32, ctrlc_mode: CtrlcMode, tx: &mpsc::SyncSender<Event>) {
+    let mut muted = false;
+    while let Ok(event) = event::read() {
+        let event::Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            continue;
+        };
+        match code {
+            // Raw mode does not turn ctrl+C into a signal.
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                cancel(tx, ctrlc_mode);
+                continue;
+            }
+            KeyCode::Char('+') => amplitude = (amplitude * STEP).min(100.0),
+            KeyCode::Char('-') => amplitude = (amplitude / STEP).max(0.01),
+            KeyCode::Char('m') => muted = !muted,
+            _ => continue,
+        }
+        gain.set(if muted { 0.0 } else { amplitude * 0.01 });
+        let muted = if muted { " (muted)" } else { "" };
+        eprint!("\rAmplitude: {amplitude:.2}%{muted}    ");
+    }
+}
+
+/// Each keypress changes the amplitude by 1 dB
+const STEP: f32 = 1.122_018_5;
@@ -0,0 +1,183 @@
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+/// The peak and the RMS level of the samples in dBFS
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Level {
+    /// The level of the loudest sample
+    pub peak: f64,
+    /// The root mean square level
+    pub rms: f64,
+}
+
+/// The levels that one or more [`Meter`]s collected
+///
+/// Share it in an [`Arc`] between the audio thread and the threads that read the levels. Reading
+/// the levels never blocks the audio thread: if they are being read while a meter wants to publish
+/// its samples, the meter keeps them until the next block.
+#[derive(Debug, Default)]
+pub struct Levels(Mutex<Block>);
+
+impl Levels {
+    /// The levels since the last call, if any samples were played since
+    ///
+    /// With more than one reader, each reader only gets the samples since any reader's last call.
+    pub fn take(&self) -> Option<Level> {
+        let block = std::mem::take(&mut *self.0.lock().ok()?);
+        if block.count == 0 {
+            return None;
+        }
+        #[expect(clippy::cast_precision_loss)]
+        let rms = (block.sum / block.count as f64).sqrt();
+        Some(Level {
+            peak: dbfs(f64::from(block.peak)),
+            rms: dbfs(rms),
+        })
+    }
+
+    /// Call `callback` with the levels every `interval` in a background thread, until it returns
+    /// `false`
+    ///
+    /// The `callback` gets `None` for intervals without any played samples, e.g. while the output
+    /// stream is paused or after it ended, so it can end the thread even then. To receive the
+    /// levels over a channel, pass `move |level| tx.send(level).is_ok()`, and the thread ends at the
+    /// first interval after the receiver was dropped.
+    pub fn watch(
+        self: Arc<Self>,
+        interval: Duration,
+        mut callback: impl FnMut(Option<Level>) -> bool + Send + 'static,
+    ) -> io::Result<thread::JoinHandle<()>> {
+        thread::Builder::new().name("levels".into()).spawn(move || {
+            loop {
+                thread::sleep(interval);
+                if !callback(self.take()) {
+                    break;
+                }
+            }
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Block {
+    peak: f32,
+    sum: f64,
+    count: u64,
+}
+
+/// Collects the levels of a source into [`Levels`], and passes the samples on unchanged
+///
+/// The levels are published once per 1024 samples, and only if that does not block the audio
+/// thread.
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use noisy_silence::{Levels, Meter, NoiseValue};
+///
+/// let levels = Arc::new(Levels::default());
+/// let source = noisy_silence::source(NoiseValue::Pink, 48_000, [0; 16], 10.0).unwrap();
+/// let source = Meter::new(source, Arc::clone(&levels));
+/// // Add the source to a mixer, or a `rodio::Sink` to pause and stop it.
+/// let _: Vec<f32> = source.take(4096).collect();
+/// assert!(levels.take().is_some_and(|level| level.peak < 0.0));
+/// ```
+#[derive(Debug)]
+pub struct Meter<S> {
+    input: S,
+    levels: Arc<Levels>,
+    block: Block,
+}
+
+impl<S: Source> Meter<S> {
+    /// Collect the levels of `input` into `levels`
+    pub fn new(input: S, levels: Arc<Levels>) -> Self {
+        Self {
+            input,
+            levels,
+            block: Block::default(),
+        }
+    }
+}
+
+impl<S: Source> Iterator for Meter<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        self.block.peak = self.block.peak.max(sample.abs());
+        self.block.sum += f64::from(sample * sample);
+        self.block.count += 1;
+        if self.block.count >= BLOCK_LEN
+            && let Ok(mut levels) = self.levels.0.try_lock()
+        {
+            levels.peak = levels.peak.max(self.block.peak);
+            levels.sum += self.block.sum;
+            levels.count += self.block.count;
+            self.block = Block::default();
+        }
+        Some(sample)
+    }
+}
+
+impl<S: Source> Source for Meter<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+/// The `level` relative to the full scale in dB, silence is clamped to -200 dBFS
+pub fn dbfs(level: f64) -> f64 {
+    20.0 * level.max(1e-10).log10()
+}
+
+/// How many samples are collected before they are published
+const BLOCK_LEN: u64 = 1024;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn watch_ends_without_samples() {
+        let levels = Arc::new(Levels::default());
+        let (tx, rx) = mpsc::channel();
+        let thread = levels
+            .watch(Duration::from_millis(1), move |level| {
+                tx.send(level).is_ok()
+            })
+            .unwrap();
+        drop(rx);
+        // No samples are ever played, but the thread still notices the dropped receiver.
+        thread.join().unwrap();
+    }
+}
@@ -0,0 +1,300 @@
+//! The noise generators behind the `noisy-silence` command line tool
+//!
+//! [`source()`] returns a [`rodio::Source`] that plays an endless stream of the selected noise
+//! type, e.g. to add it to the mixer of a [`rodio`] output stream.
+//!
+//! ```
+//! use noisy_silence::NoiseValue;
+//! use rodio::Source;
+//!
+//! let noise: NoiseValue = "pink".parse().unwrap();
+//! let source = noisy_silence::source(noise, 48_000, [0; 16], 10.0).unwrap();
+//! assert_eq!(source.channels(), 1);
+//! ```
+//!
+//! Use [`NoiseValue::all()`] to let your users select the noise type, and
+//! [`NoiseValue::to_noise()`] to get the unamplified noise generator. [`balanced_source()`] makes
+//! every noise type sound about equally loud at the same amplitude.
+//!
+//! To drive a level meter, wrap the source in a [`Meter`], and read the peak and RMS level from
+//! its shared [`Levels`], e.g. every 100 ms with [`Levels::watch()`].
+
+mod brownian;
+mod dc;
+mod grey;
+mod levels;
+mod rng;
+mod slope;
+mod velvet;
+mod voss;
+
+use std::num::FpCategory;
+use std::time::Duration;
+
+#[cfg(feature = "jack")]
+use cpal as _;
+#[cfg(all(feature = "cli", not(unix)))]
+use ctrlc as _;
+use rodio::source::{Amplify, SeekError, noise};
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+use strum::VariantArray;
+// The dependencies of the command line interface are not used by the library.
+#[cfg(feature = "cli")]
+use {
+    clap_complete as _, crossterm as _, dirs as _, hound as _, humantime as _,
+    pretty_error_debug as _, rosc as _, siphasher as _, starship_battery as _, toml as _,
+    tracing as _, tracing_subscriber as _,
+};
+#[cfg(all(feature = "cli", unix))]
+use {sd_notify as _, signal_hook as _};
+
+pub use crate::brownian::LeakyBrownian;
+pub use crate::dc::DcBlocker;
+pub use crate::grey::GreyNoise;
+pub use crate::levels::{Level, Levels, Meter, dbfs};
+pub use crate::rng::{NoiseRng, RngValue};
+pub use crate::slope::SlopeNoise;
+pub use crate::velvet::VelvetNoise;
+pub use crate::voss::VossNoise;
+
+nodyn::nodyn! {
+    /// A noise generator of any of the supported noise types
+    ///
+    /// A mono [`Source`] and an endless [`Iterator`] of samples, use [`NoiseValue::to_noise()`] to
+    /// create one.
+    #[derive(Debug)]
+    pub enum Noise {
+        /// Uniformly distributed white noise
+        White(noise::WhiteUniform<NoiseRng>),
+        /// Normally distributed white noise
+        Gaussian(noise::WhiteGaussian<NoiseRng>),
+        /// Triangularly distributed white noise
+        Triangular(noise::WhiteTriangular<NoiseRng>),
+        /// Pink noise
+        Pink(noise::Pink<NoiseRng>),
+        /// Pink noise of the Voss-McCartney algorithm
+        Voss(VossNoise<NoiseRng>),
+        /// Blue noise
+        Blue(noise::Blue<NoiseRng>),
+        /// Violet noise
+        Violet(noise::Violet<NoiseRng>),
+        /// Brownian noise, without its DC offset
+        Brownian(DcBlocker<noise::Brownian<NoiseRng>>),
+        /// Brownian noise with an adjustable leak, without its DC offset
+        LeakyBrownian(DcBlocker<LeakyBrownian<NoiseRng>>),
+        /// Velvet noise
+        Velvet(VelvetNoise<NoiseRng>),
+        /// Grey noise
+        Grey(GreyNoise<NoiseRng>),
+    }
+
+    impl Iterator {
+        type Item = Sample;
+
+        fn next(&mut self) -> Option<Self::Item>;
+    }
+
+    impl Source {
+        fn current_span_len(&self) -> Option<usize>;
+        fn channels(&self) -> ChannelCount;
+        fn sample_rate(&self) -> SampleRate;
+        fn total_duration(&self) -> Option<Duration>;
+
+        #[inline]
+        fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+            Ok(())
+        }
+    }
+}
+
+/// The supported noise types
+///
+/// The type can be displayed as and parsed from its lowercase name, e.g. `"brownian"`, ignoring
+/// the case when parsing.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::Display,
+    strum::EnumString,
+    strum::VariantArray,
+)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum NoiseValue {
+    /// Uniformly distributed white noise
+    White,
+    /// Normally distributed white noise
+    Gaussian,
+    /// Triangularly distributed white noise
+    Triangular,
+    /// Pink noise, -3 dB per octave
+    Pink,
+    /// Blue noise, +3 dB per octave
+    Blue,
+    /// Violet noise, +6 dB per octave
+    Violet,
+    /// Brownian noise, -6 dB per octave, also called "red" or "brown" noise
+    #[default]
+    #[strum(to_string = "brownian", serialize = "red", serialize = "brown")]
+    #[cfg_attr(feature = "cli", value(aliases = ["red", "brown"]))]
+    Brownian,
+    /// Velvet noise, sparse random impulses
+    Velvet,
+    /// Grey noise, white noise that sounds about equally loud at every frequency
+    Grey,
+}
+
+impl NoiseValue {
+    /// Every supported noise type
+    #[must_use]
+    pub const fn all() -> &'static [Self] {
+        <Self as VariantArray>::VARIANTS
+    }
+
+    /// A gain that makes every noise type sound about as loud as brownian noise
+    ///
+    /// The gains were chosen by comparing the A-weighted power of the noise types. Multiply the
+    /// output of [`to_noise()`](Self::to_noise) with it to switch between noise types at the same
+    /// amplitude without getting startled.
+    #[must_use]
+    pub const fn loudness_gain(self) -> f32 {
+        match self {
+            Self::White => 0.28,
+            Self::Gaussian | Self::Blue => 0.42,
+            Self::Triangular => 0.38,
+            Self::Pink => 1.36,
+            Self::Violet => 0.29,
+            Self::Brownian => 1.0,
+            Self::Velvet => 0.76,
+            Self::Grey => 0.40,
+        }
+    }
+
+    /// A one-line description of how this noise type sounds, and what it is good for
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::White => "Equal power at every frequency, a bright hiss that masks speech well",
+            Self::Gaussian => "White noise with a bell curve distribution, sounds just like white",
+            Self::Triangular => "White noise with a triangular distribution, used as dither",
+            Self::Pink => "Equal power per octave, a balanced rush like rain or a waterfall",
+            Self::Blue => "Rising by 3 dB per octave, a sharp hiss without any rumble",
+            Self::Violet => "Rising by 6 dB per octave, a thin sizzle to mask tinnitus",
+            Self::Brownian => "Falling by 6 dB per octave, a deep rumble like surf, good for sleep",
+            Self::Velvet => "Sparse random clicks, smoother than white noise, used for reverbs",
+            Self::Grey => "Sounds equally loud at every frequency, a neutral, even hush",
+        }
+    }
+
+    /// A generator for this noise type, the same `seed` always generates the same noise
+    ///
+    /// It uses the default random number generator, see [`to_noise_with_rng()`] to select another.
+    ///
+    /// [`to_noise_with_rng()`]: Self::to_noise_with_rng
+    #[must_use]
+    pub fn to_noise(self, sample_rate: SampleRate, seed: [u8; 16]) -> Noise {
+        self.to_noise_with_rng(sample_rate, RngValue::default().to_rng(seed))
+    }
+
+    /// A generator for this noise type, which draws its random numbers from `rng`
+    #[must_use]
+    pub fn to_noise_with_rng(self, sample_rate: SampleRate, rng: NoiseRng) -> Noise {
+        let func: fn(SampleRate, NoiseRng) -> Noise = match self {
+            Self::White => |s, r| Noise::White(noise::WhiteUniform::new_with_rng(s, r)),
+            Self::Gaussian => |s, r| Noise::Gaussian(noise::WhiteGaussian::new_with_rng(s, r)),
+            Self::Triangular => {
+                |s, r| Noise::Triangular(noise::WhiteTriangular::new_with_rng(s, r))
+            }
+            Self::Pink => |s, r| Noise::Pink(noise::Pink::new_with_rng(s, r)),
+            Self::Blue => |s, r| Noise::Blue(noise::Blue::new_with_rng(s, r)),
+            Self::Violet => |s, r| Noise::Violet(noise::Violet::new_with_rng(s, r)),
+            Self::Brownian => {
+                |s, r| Noise::Brownian(DcBlocker::new(noise::Brownian::new_with_rng(s, r)))
+            }
+            Self::Velvet => |s, r| Noise::Velvet(VelvetNoise::new_with_rng(s, r)),
+            Self::Grey => |s, r| Noise::Grey(GreyNoise::new_with_rng(s, r)),
+        };
+        func(sample_rate, rng)
+    }
+}
+
+/// A mono source of the `noise` type with an `amplitude` in percent
+///
+/// See [`balanced_source()`] to make the noise types sound about equally loud.
+///
+/// # Errors
+///
+/// Fails if `amplitude` is not between 0.01% and 100%.
+pub fn source(
+    noise: NoiseValue,
+    sample_rate: SampleRate,
+    seed: [u8; 16],
+    amplitude: f32,
+) -> Result<Amplify<Noise>, Error> {
+    let amplitude = check_amplitude(amplitude)?;
+    Ok(noise.to_noise(sample_rate, seed).amplify(amplitude * 0.01))
+}
+
+/// Like [`source()`], but the amplitude is adjusted with [`NoiseValue::loudness_gain()`]
+///
+/// # Errors
+///
+/// Fails if `amplitude` is not between 0.01% and 100%.
+pub fn balanced_source(
+    noise: NoiseValue,
+    sample_rate: SampleRate,
+    seed: [u8; 16],
+    amplitude: f32,
+) -> Result<Amplify<Noise>, Error> {
+    let amplitude = check_amplitude(amplitude)?;
+    let gain = amplitude * 0.01 * noise.loudness_gain();
+    Ok(noise.to_noise(sample_rate, seed).amplify(gain))
+}
+
+/// Ensure that the `amplitude` in percent is between 0.01% and 100%
+///
+/// # Errors
+///
+/// Fails if `amplitude` is out of range, or not a number.
+pub fn check_amplitude(amplitude: f32) -> Result<f32, Error> {
+    if let FpCategory::Normal | FpCategory::Subnormal = amplitude.classify()
+        && (0.01..=100.0).contains(&amplitude)
+    {
+        Ok(amplitude)
+    } else {
+        Err(Error::Amplitude(amplitude))
+    }
+}
+
+/// An error returned by this library
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error, displaydoc::Display)]
+#[non_exhaustive]
+pub enum Error {
+    /// Unsupported amplitude {0:?}
+    Amplitude(f32),
+    /// The slope {0:?} is not in the range between 0 and 2
+    Slope(f32),
+    /// The velvet noise density {0:?} is not in the range between 0 and the sample rate {1}
+    VelvetDensity(f32, SampleRate),
+    /// The brownian leak {0:?} is not in the range between 0 and 1
+    BrownianLeak(f32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loudness_gains_are_positive() {
+        for &noise in NoiseValue::all() {
+            let gain = noise.loudness_gain();
+            assert!(gain.is_finite() && gain > 0.0, "{noise}: {gain}");
+        }
+        assert!((NoiseValue::Brownian.loudness_gain() - 1.0).abs() < f32::EPSILON);
+    }
+}
@@ -1,79 +1,1225 @@
 #![doc = include_str!("../README.md")]
 
-use std::io::{Write, stdout};
-use std::num::FpCategory;
+mod battery;
+mod compressor;
+mod config;
+mod crossfeed;
+mod duty;
+mod envelope;
+mod fade;
+mod filter;
+mod floor;
+mod idle;
+mod interactive;
+mod meter;
+mod metrics;
+mod mix;
+mod osc;
+mod quantize;
+mod reload;
+mod rotate;
+mod signals;
+mod spectrum;
+mod stereo;
+mod systemd;
+mod tee;
+mod tremolo;
+mod volume;
+mod watchdog;
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::io::{BufWriter, ErrorKind, IsTerminal, Write, stdout};
+use std::path::{Path, PathBuf};
 use std::process::{abort, exit};
-use std::sync::atomic::AtomicU32;
-use std::sync::atomic::Ordering::SeqCst;
-use std::sync::mpsc;
-use std::time::Duration;
+use std::sync::atomic::Ordering::{Relaxed, SeqCst};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64};
+use std::sync::{Arc, mpsc};
+use std::time::{Duration, Instant};
 
 use clap::Parser;
-use rand::SeedableRng;
-use rand_xoshiro::Xoroshiro128Plus;
-use rodio::source::{SeekError, noise};
-use rodio::{ChannelCount, Sample, SampleRate, Source};
-use tracing::{info, warn};
+use noisy_silence::{
+    DcBlocker, LeakyBrownian, Levels, Meter, Noise, NoiseValue, RngValue, SlopeNoise, VelvetNoise,
+    VossNoise,
+};
+use rand::rngs::OsRng;
+use rand::{Rng, TryRngCore};
+use rodio::cpal::BufferSize;
+use rodio::cpal::traits::HostTrait;
+use rodio::source::{Function, SignalGenerator, Zero};
+use rodio::{ChannelCount, DeviceTrait, Sample, SampleRate, Source};
+use siphasher::sip128::SipHasher13;
+use tracing::level_filters::LevelFilter;
+use tracing::{debug, info, warn};
 use tracing_subscriber::util::SubscriberInitExt;
+// The dependencies of the library are not used by the binary directly.
+use {nodyn as _, rand_chacha as _, strum as _};
+
+use crate::compressor::Compressor;
+use crate::crossfeed::Crossfeed;
+use crate::duty::{DutyCycle, Intermittent, spawn_phase_log};
+use crate::envelope::Envelope;
+use crate::fade::{FadeOut, RampUp, Timed};
+use crate::filter::{Biquad, Coefficients, Tunable};
+use crate::floor::{Floor, Tap};
+use crate::mix::{CrossfadeNoise, MixedNoise, Slot, Switch};
+use crate::quantize::{Dither, Quantizer, Requantize, SampleFormat, StreamFormat};
+use crate::stereo::{Haas, Interleave, MAX_AUTO_PAN_RATE, Pan, Replicate, Width};
+use crate::tee::{Recorder, Tee};
+use crate::tremolo::{MAX_LFO_RATE, Tremolo};
+use crate::volume::{Headroom, Limiter, Volume, report_headroom, warn_limited};
 
 fn main() -> Result<(), Error> {
-    let args = Args::parse();
+    let mut args = config::parse_args()?;
+    if let Some(result) = print_info(&args) {
+        return result;
+    }
+
+    init_tracing(&args)?;
+    if let Some(path) = &args.config {
+        debug!("Read settings from {}.", path.display());
+    }
+
+    let (tx, rx) = mpsc::sync_channel(1);
+    // Services get shut down gracefully with SIGTERM, too.
+    signals::install(tx.clone(), args.ctrlc_mode, config::has_file(&args))?;
+
+    let amplitude = amplitude(&args)?;
+    let battery_amplitude = args
+        .battery_amplitude
+        .map(noisy_silence::check_amplitude)
+        .transpose()
+        .map_err(Error::Noise)?;
+    let envelope = args.envelope.as_deref().map(Envelope::read).transpose()?;
+    let seed = seed(&args)?;
+    debug!("Using seed {seed:02x?}.");
+
+    if args.dry_run {
+        let shared = Shared::new(amplitude, false, false);
+        let sample_rate = args.sample_rate.unwrap_or(DEFAULT_SAMPLE_RATE);
+        let channels = args.channels.unwrap_or(1);
+        let source = build_source(&args, sample_rate, channels, &shared, seed, tx.clone())?;
+        dry_run(&args, source, &shared.counters);
+        report_headroom(&shared.counters);
+        warn_limited(&shared.counters);
+        return Ok(());
+    }
+
+    if let Some(path) = &args.output
+        && !args.tee
+    {
+        let shared = Shared::new(amplitude, false, false);
+        let sample_rate = args.sample_rate.unwrap_or(DEFAULT_SAMPLE_RATE);
+        let channels = args.channels.unwrap_or(1);
+        let source = build_source(&args, sample_rate, channels, &shared, seed, tx.clone())?;
+        let result = write_output(&args, path, source, amplitude, seed, &shared.fading, &rx);
+        report_headroom(&shared.counters);
+        warn_limited(&shared.counters);
+        return result;
+    }
+
+    let mut shared = Shared::new(
+        amplitude,
+        args.meter && !args.quiet,
+        args.metrics_addr.is_some(),
+    );
+    let recorder = match &args.output {
+        Some(path) if path == Path::new("-") => return Err(Error::TeeStdout),
+        Some(path) => Some(Recorder::spawn(
+            path,
+            quantizer(&args, SampleFormat::S16, seed),
+        )?),
+        None => None,
+    };
+    shared.recording = recorder.as_ref().map(Recorder::sender);
+    let mut stream = Some(play(&args, &shared, seed, &tx)?);
+
+    info!(
+        "Now playing {} with an amplitude of {amplitude:.2}%.",
+        args.sound(),
+    );
+    if let Some(delay) = args.delay {
+        info!("Noise will begin in {}.", humantime::format_duration(delay));
+    }
+    if !args.quiet {
+        eprintln!("Press ctrl+C to end the process.");
+    }
+    systemd::ready();
+    let amplitudes = (amplitude, battery_amplitude, envelope);
+    let metrics = spawn_helpers(&args, &shared, amplitudes, &tx)?;
+    let raw_mode = interactive(&args, &shared, amplitude, &tx)?;
+
+    // The settings as they were read, before any are changed while playing.
+    let mut loaded = args.clone();
+    let started = Instant::now();
+    let (duration, delay) = (args.duration, args.delay.unwrap_or_default());
+    let event = loop {
+        match rx.recv() {
+            Ok(Event::Disconnected) => warn!("Lost the output device, trying to reconnect."),
+            Ok(Event::Stalled) => warn!("The audio stream stalled, restarting it."),
+            Ok(Event::Reload) => {
+                reload::reload(&mut args, &mut loaded, &shared);
+                continue;
+            }
+            Ok(Event::Noise(noise)) => {
+                switch_noise(&mut args, &shared, noise);
+                continue;
+            }
+            event => break event,
+        }
+        drop(stream.take());
+        // Only wait and play the noise for the remaining time after reconnecting.
+        let elapsed = started.elapsed();
+        args.delay = Some(delay.saturating_sub(elapsed));
+        args.duration =
+            duration.map(|duration| duration.saturating_sub(elapsed.saturating_sub(delay)));
+        stream = reconnect(&args, &shared, seed, &tx, &rx)?;
+        if stream.is_none() {
+            break Ok(Event::Cancelled);
+        }
+    };
+    drop(raw_mode);
+    systemd::stopping();
+    match event {
+        Ok(Event::Elapsed) => info!("Duration elapsed, closing stream."),
+        Ok(
+            Event::Cancelled
+            | Event::FadedOut
+            | Event::Disconnected
+            | Event::Stalled
+            | Event::Reload
+            | Event::Noise(_),
+        )
+        | Err(mpsc::RecvError) => {
+            if !args.quiet {
+                eprintln!();
+            }
+            info!("Closing stream and exiting.");
+            // There is nothing to fade out if the noise did not begin yet.
+            if started.elapsed() >= delay {
+                shared.fading.store(true, Relaxed);
+                // Wait for the fade-out to finish, unless the audio thread is stuck,
+                // or the user is impatient and pressed ctrl+C again.
+                let _: Result<Event, mpsc::RecvTimeoutError> =
+                    rx.recv_timeout(args.fade_out + Duration::from_secs(1));
+            }
+        }
+    }
+    drop(stream);
+    report_headroom(&shared.counters);
+    warn_limited(&shared.counters);
+    drop(metrics);
+    if let Some(recorder) = recorder {
+        recorder.finish()?;
+    }
+    if args.remember {
+        // A muted or drifting gain is no amplitude to start with.
+        let gain = shared.gain.get() * 100.0;
+        let amplitude = match (&args.battery_amplitude, &args.envelope) {
+            (None, None) if noisy_silence::check_amplitude(gain).is_ok() => gain,
+            _ => amplitude,
+        };
+        // Once the crossfade completed, its target is playing, unless the noise type was switched.
+        let noise = match args.crossfade_to {
+            Some(target) if started.elapsed() >= delay + args.crossfade_duration => target,
+            _ => args.noise,
+        };
+        let path = config::remember(noise, amplitude)?;
+        info!("Remembered the settings in {}.", path.display());
+    }
+    Ok(())
+}
+
+/// Start the requested background threads that watch or control the stream
+fn spawn_helpers(
+    args: &Args,
+    shared: &Shared,
+    (amplitude, battery_amplitude, envelope): (f32, Option<f32>, Option<Envelope>),
+    tx: &mpsc::SyncSender<Event>,
+) -> Result<Option<metrics::Server>, Error> {
+    if let Some(levels) = &shared.levels {
+        meter::spawn(Arc::clone(levels))?;
+    }
+    if let Some(timeout) = args.watchdog_timeout {
+        let delay = args.delay.unwrap_or_default();
+        watchdog::spawn(Arc::clone(&shared.gain), timeout, delay, tx.clone())?;
+    }
+    if let Some(battery_amplitude) = battery_amplitude {
+        battery::spawn(Arc::clone(&shared.gain), amplitude, battery_amplitude)?;
+    }
+    if let Some(envelope) = envelope {
+        let start = Instant::now() + args.delay.unwrap_or_default();
+        envelope::spawn(envelope, Arc::clone(&shared.gain), start)?;
+    }
+    if let Some(after) = args.pause_after_idle {
+        idle::spawn(Arc::clone(&shared.gain), after)?;
+    }
+    if let Some(addr) = &args.osc_listen {
+        osc::spawn(addr, Arc::clone(&shared.gain), tx.clone())?;
+    }
+    if let Some(interval) = args.rotate_every {
+        let noises = match args.rotate.as_slice() {
+            [] => NoiseValue::all().to_vec(),
+            noises => noises.to_vec(),
+        };
+        // The first switch ends a `--crossfade-to`, so continue the rotation after its target.
+        let current = args.crossfade_to.unwrap_or(args.noise);
+        rotate::spawn(noises, current, interval, tx.clone())?;
+    }
+    match (&args.metrics_addr, &shared.metrics_levels) {
+        (Some(addr), Some(levels)) => Ok(Some(metrics::spawn(
+            addr,
+            Arc::clone(&shared.gain),
+            Arc::clone(levels),
+            Arc::clone(&shared.counters),
+        )?)),
+        _ => Ok(None),
+    }
+}
+
+/// Let the user change the amplitude with the keyboard if `--interactive` was requested
+fn interactive(
+    args: &Args,
+    shared: &Shared,
+    amplitude: f32,
+    tx: &mpsc::SyncSender<Event>,
+) -> Result<Option<interactive::RawMode>, Error> {
+    if !args.interactive {
+        return Ok(None);
+    }
+    if !args.quiet {
+        eprintln!("Press + or - to change the amplitude, and m to mute or unmute the noise.");
+    }
+    let raw_mode = interactive::RawMode::enable()?;
+    interactive::spawn(
+        Arc::clone(&shared.gain),
+        amplitude,
+        args.ctrlc_mode,
+        tx.clone(),
+    )?;
+    Ok(Some(raw_mode))
+}
+
+/// Handle the options that only print some information instead of playing noise
+fn print_info(args: &Args) -> Option<Result<(), Error>> {
     if args.license {
         let _: std::io::Result<()> = stdout()
             .lock()
             .write_all(include_str!("../LICENSE.ISC").as_bytes());
-        return Ok(());
+        Some(Ok(()))
+    } else if args.list_devices {
+        Some(list_devices(args.backend))
+    } else if args.list_noise_types {
+        list_noise_types();
+        Some(Ok(()))
+    } else if args.list_presets {
+        Some(config::list_presets(args.config.as_deref()))
+    } else if let Some(noise) = args.info {
+        Some(print_spectrum(args, noise))
+    } else if let Some(shell) = args.completions {
+        print_completions(shell);
+        Some(Ok(()))
+    } else {
+        None
     }
+}
+
+/// Print a completion script for `shell` to stdout
+fn print_completions(shell: clap_complete::Shell) {
+    // `generate()` panics if it cannot write, e.g. into a closed pipe.
+    let mut script = Vec::new();
+    clap_complete::generate(
+        shell,
+        &mut config::command(),
+        env!("CARGO_BIN_NAME"),
+        &mut script,
+    );
+    let _: std::io::Result<()> = stdout().lock().write_all(&script);
+}
 
+/// Set up the log output
+fn init_tracing(args: &Args) -> Result<(), Error> {
+    let level = if args.quiet {
+        LevelFilter::WARN
+    } else {
+        LevelFilter::INFO
+    };
     let filter = tracing_subscriber::EnvFilter::builder()
-        .with_default_directive(tracing::level_filters::LevelFilter::INFO.into())
+        .with_default_directive(level.into())
         .from_env()?;
-    tracing_subscriber::fmt()
+    let ansi = match args.color {
+        Color::Auto => {
+            // Cf. <https://no-color.org/>, an empty value does not count.
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+            std::io::stderr().is_terminal() && !no_color
+        }
+        Color::Always => true,
+        Color::Never => false,
+    };
+    // Keep stdout free for `--output -`.
+    let subscriber = tracing_subscriber::fmt()
         .with_env_filter(filter)
-        .compact()
-        .finish()
-        .try_init()?;
+        .with_ansi(ansi)
+        .with_writer(std::io::stderr);
+    match args.log_format {
+        LogFormat::Text => subscriber.compact().finish().try_init()?,
+        LogFormat::Json => subscriber.json().finish().try_init()?,
+    }
+    Ok(())
+}
 
-    let cancelled = AtomicU32::new(0);
-    let (tx, rx) = mpsc::sync_channel(1);
-    ctrlc::try_set_handler(move || {
-        match cancelled.fetch_add(1, SeqCst) {
-            0 => {}
-            1 => warn!("Trapped ctrl+C twice. Press ctrc+C again to kill the process."),
-            2 => exit(0),
-            _ => abort(),
-        }
-        let _: Result<(), mpsc::SendError<()>> = tx.send(());
-    })?;
+/// The amplitude in percent, from either `--amplitude-db` or the positional argument
+fn amplitude(args: &Args) -> Result<f32, Error> {
+    if !args.channel_amplitudes.is_empty() {
+        // The loudest channel plays at the amplitude, see `channel_balance()`.
+        let mut loudest = 0f32;
+        for &amplitude in &args.channel_amplitudes {
+            loudest = loudest.max(noisy_silence::check_amplitude(amplitude).map_err(Error::Noise)?);
+        }
+        return Ok(loudest);
+    }
+    let amplitude = match args.amplitude_db {
+        Some(db) => 10f32.powf(db / 20.0) * 100.0,
+        None => args.amplitude,
+    };
+    noisy_silence::check_amplitude(amplitude).map_err(Error::Noise)
+}
+
+/// The seed for the noise generators, from `--seed` or `--random-seed`
+fn seed(args: &Args) -> Result<[u8; 16], Error> {
+    if let Some(seed) = &args.seed {
+        Ok(hash_seed(seed))
+    } else if args.random_seed {
+        let mut bytes = [0; 16];
+        OsRng.try_fill_bytes(&mut bytes)?;
+        let seed = format!("{:032x}", u128::from_le_bytes(bytes));
+        info!("Using random seed {seed:?}. Run with `--seed {seed}` to hear this noise again.");
+        Ok(hash_seed(&seed))
+    } else {
+        Ok(SEED)
+    }
+}
 
-    let amplitude = if let FpCategory::Normal | FpCategory::Subnormal = args.amplitude.classify()
-        && (0.01..=100.0).contains(&args.amplitude)
+/// Open the output stream, and start playing the configured noise
+///
+/// With `--all-devices`, every device of the `--device` list gets a stream of its own.
+fn play(
+    args: &Args,
+    shared: &Shared,
+    seed: [u8; 16],
+    tx: &mpsc::SyncSender<Event>,
+) -> Result<Vec<rodio::OutputStream>, Error> {
+    shared.switches.borrow_mut().clear();
+    shared.filters.borrow_mut().clear();
+    let open = |devices: &[String], sample_rate| {
+        open_stream(
+            args.backend,
+            devices,
+            sample_rate,
+            args.sample_format,
+            args.buffer_frames,
+            tx,
+        )
+    };
+    if !args.all_devices {
+        let stream = open(&args.device, args.sample_rate)?;
+        let sample_rate = args.sample_rate.unwrap_or(stream.config().sample_rate());
+        let recording = shared.recording.as_ref();
+        start(args, shared, &stream, sample_rate, seed, recording, tx)?;
+        return Ok(vec![stream]);
+    }
+    let mut streams = Vec::with_capacity(args.device.len());
+    let mut sample_rate = args.sample_rate;
+    for (index, device) in args.device.iter().enumerate() {
+        let stream = open(std::slice::from_ref(device), sample_rate)?;
+        // Generate the noise for every device with the same rate, so the same seed plays the
+        // same noise everywhere.
+        let sample_rate = *sample_rate.get_or_insert(stream.config().sample_rate());
+        let seed = device_seed(args, seed, index);
+        // Only record one of the streams, they would be interleaved otherwise.
+        let recording = shared.recording.as_ref().filter(|_| index == 0);
+        start(args, shared, &stream, sample_rate, seed, recording, tx)?;
+        streams.push(stream);
+    }
+    Ok(streams)
+}
+
+/// Add the configured noise to the mixer of `stream`
+fn start(
+    args: &Args,
+    shared: &Shared,
+    stream: &rodio::OutputStream,
+    sample_rate: SampleRate,
+    seed: [u8; 16],
+    recording: Option<&tee::Recording>,
+    tx: &mpsc::SyncSender<Event>,
+) -> Result<(), Error> {
+    let channels = args.channels.unwrap_or(stream.config().channel_count());
+    if args.binaural.is_some() && channels < 2 {
+        return Err(Error::BinauralChannels(channels));
+    }
+    let source = build_source(args, sample_rate, channels, shared, seed, tx.clone())?;
+    let source: BoxedSource = match args.sample_format {
+        Some(StreamFormat::I16 | StreamFormat::U16) => {
+            let quantizer = Quantizer::new(SampleFormat::S16, dither(args), seed);
+            Box::new(Requantize::new(source, quantizer))
+        }
+        Some(StreamFormat::F32) | None => Box::new(source),
+    };
+    let source: BoxedSource = match recording {
+        Some(recording) => Box::new(Tee::new(source, recording.clone())),
+        None => Box::new(source),
+    };
+    let source: BoxedSource = match &shared.metrics_levels {
+        Some(levels) => Box::new(Meter::new(source, Arc::clone(levels))),
+        None => Box::new(source),
+    };
+    match &shared.levels {
+        Some(levels) => stream.mixer().add(Meter::new(source, Arc::clone(levels))),
+        None => stream.mixer().add(source),
+    }
+    Ok(())
+}
+
+/// The seed of the noise on the device at `index` of `--all-devices`
+fn device_seed(args: &Args, seed: [u8; 16], index: usize) -> [u8; 16] {
+    if !args.decorrelate_devices || index == 0 {
+        return seed;
+    }
+    SipHasher13::new_with_key(&seed)
+        .hash(format!("device {index}").as_bytes())
+        .as_bytes()
+}
+
+/// Try to open the output stream again after the device was lost, with an exponential backoff
+///
+/// Returns `None` if the user pressed ctrl+C in the meantime.
+fn reconnect(
+    args: &Args,
+    shared: &Shared,
+    seed: [u8; 16],
+    tx: &mpsc::SyncSender<Event>,
+    rx: &mpsc::Receiver<Event>,
+) -> Result<Option<Vec<rodio::OutputStream>>, Error> {
+    let mut delay = Duration::from_millis(500);
+    let mut last_error = None;
+    for attempt in 1..=args.reconnect_retries {
+        // Also drops the stale events from the lost stream.
+        let deadline = Instant::now() + delay;
+        while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+            if let Ok(Event::Cancelled) = rx.recv_timeout(timeout) {
+                return Ok(None);
+            }
+        }
+        match play(args, shared, seed, tx) {
+            Ok(stream) => {
+                info!("Reconnected to the output device.");
+                let _: u32 = shared.counters.reconnects.fetch_add(1, Relaxed);
+                return Ok(Some(stream));
+            }
+            Err(err) => {
+                warn!(
+                    "Could not reconnect to the output device (attempt {attempt} of {}): {err}",
+                    args.reconnect_retries,
+                );
+                last_error = Some(err);
+            }
+        }
+        delay = (delay * 2).min(Duration::from_secs(30));
+    }
+    match last_error {
+        Some(err) => Err(Error::Reconnect(args.reconnect_retries, Box::new(err))),
+        None => Err(Error::Disconnected),
+    }
+}
+
+/// Open the first of the selected output devices that works, or the default output device,
+/// preferably with the requested sample rate and buffer size, and with the requested sample format
+///
+/// Stream errors, e.g. if the device gets unplugged, are reported as [`Event::Disconnected`].
+fn open_stream(
+    backend: Backend,
+    devices: &[String],
+    sample_rate: Option<SampleRate>,
+    sample_format: Option<StreamFormat>,
+    buffer_frames: Option<u32>,
+    tx: &mpsc::SyncSender<Event>,
+) -> Result<rodio::OutputStream, Error> {
+    let on_error = {
+        let tx = tx.clone();
+        move |err| {
+            warn!("Error in the audio stream: {err}");
+            let _: Result<(), mpsc::TrySendError<Event>> = tx.try_send(Event::Disconnected);
+        }
+    };
+    let sample_format = sample_format.map(StreamFormat::to_cpal);
+    let result = match (devices, sample_rate, sample_format, buffer_frames) {
+        ([], None, None, None) => open_default_stream(backend, on_error),
+        ([], _, _, _) => backend.default_output_device().and_then(|device| {
+            open_device_stream(device, sample_rate, sample_format, buffer_frames, on_error)
+        }),
+        ([name], _, _, _) => find_device(backend, name).and_then(|device| {
+            open_device_stream(device, sample_rate, sample_format, buffer_frames, on_error)
+        }),
+        (names, _, _, _) => {
+            let open = |name: &String| {
+                find_device(backend, name).and_then(|device| {
+                    let on_error = on_error.clone();
+                    open_device_stream(device, sample_rate, sample_format, buffer_frames, on_error)
+                })
+            };
+            open_first(backend, names, open)
+        }
+    };
+    let (device, stream) = result.inspect_err(|_| log_device_inventory(backend))?;
+
+    let config = stream.config();
+    let buffer_size = match *config.buffer_size() {
+        BufferSize::Default => "the default buffer size".to_owned(),
+        BufferSize::Fixed(frames) => format!(
+            "a buffer size of {frames} frames ({:.1} ms)",
+            f64::from(frames) * 1000.0 / f64::from(config.sample_rate()),
+        ),
+    };
+    info!(
+        "Opened output device {:?} with {} Hz, {} channels, {} samples and {buffer_size}.",
+        device.name().unwrap_or_default(),
+        config.sample_rate(),
+        config.channel_count(),
+        config.sample_format(),
+    );
+    Ok(stream)
+}
+
+/// Open `device`, preferably with the requested sample rate and buffer size
+///
+/// A requested sample format is mandatory, it is an error if the device does not support it.
+fn open_device_stream(
+    device: rodio::Device,
+    sample_rate: Option<SampleRate>,
+    sample_format: Option<rodio::cpal::SampleFormat>,
+    buffer_frames: Option<u32>,
+    on_error: impl FnMut(rodio::cpal::StreamError) + Clone + Send + 'static,
+) -> Result<(rodio::Device, rodio::OutputStream), Error> {
+    let builder = |buffer_size| -> Result<_, Error> {
+        let builder =
+            rodio::OutputStreamBuilder::from_device(device.clone())?.with_buffer_size(buffer_size);
+        Ok(match sample_format {
+            Some(sample_format) => builder.with_sample_format(sample_format),
+            None => builder,
+        })
+    };
+    if let Some(sample_format) = sample_format {
+        check_sample_format(&device, sample_format)?;
+    }
+    let buffer_size = buffer_frames.map_or(BufferSize::Default, BufferSize::Fixed);
+    if let Some(sample_rate) = sample_rate {
+        match builder(buffer_size)?
+            .with_sample_rate(sample_rate)
+            .with_error_callback(on_error.clone())
+            .open_stream()
+        {
+            Ok(stream) => return Ok((device, stream)),
+            Err(err) => warn!(
+                "The output device does not support a sample rate of {sample_rate} Hz, \
+                 the noise will be resampled: {err}",
+            ),
+        }
+    }
+    if let Some(frames) = buffer_frames {
+        match builder(buffer_size)?
+            .with_error_callback(on_error.clone())
+            .open_stream()
+        {
+            Ok(stream) => return Ok((device, stream)),
+            Err(err) => warn!(
+                "The output device does not support a buffer size of {frames} frames, \
+                 using its default buffer size: {err}",
+            ),
+        }
+    }
+    let builder = builder(BufferSize::Default)?.with_error_callback(on_error);
+    let stream = match sample_format {
+        Some(_) => builder.open_stream()?,
+        None => builder.open_stream_or_fallback()?,
+    };
+    Ok((device, stream))
+}
+
+/// Fail if `device` does not support the `sample_format`, with the list of supported formats
+fn check_sample_format(
+    device: &rodio::Device,
+    sample_format: rodio::cpal::SampleFormat,
+) -> Result<(), Error> {
+    let mut formats = Vec::new();
+    for config in device.supported_output_configs()? {
+        if config.sample_format() == sample_format {
+            return Ok(());
+        }
+        if !formats.contains(&config.sample_format()) {
+            formats.push(config.sample_format());
+        }
+    }
+    Err(Error::SampleFormat(sample_format, SampleFormats(formats)))
+}
+
+/// Like [`rodio::OutputStreamBuilder::open_default_stream()`], but with an error callback
+fn open_default_stream(
+    backend: Backend,
+    on_error: impl FnMut(rodio::cpal::StreamError) + Clone + Send + 'static,
+) -> Result<(rodio::Device, rodio::OutputStream), Error> {
+    let device = backend.default_output_device()?;
+    let err = match rodio::OutputStreamBuilder::from_device(device.clone())?
+        .with_error_callback(on_error.clone())
+        .open_stream()
     {
-        args.amplitude
+        Ok(stream) => return Ok((device, stream)),
+        Err(err) => err,
+    };
+    // Fall back to any device that works.
+    for device in backend.host()?.output_devices()? {
+        if let Ok(stream) =
+            rodio::OutputStreamBuilder::from_device(device.clone()).and_then(|builder| {
+                builder
+                    .with_error_callback(on_error.clone())
+                    .open_stream_or_fallback()
+            })
+        {
+            return Ok((device, stream));
+        }
+    }
+    Err(err.into())
+}
+
+/// Chain the configured noise with all the requested stages
+fn build_source(
+    args: &Args,
+    sample_rate: SampleRate,
+    channels: ChannelCount,
+    shared: &Shared,
+    seed: [u8; 16],
+    tx: mpsc::SyncSender<Event>,
+) -> Result<FadeOut<BoxedSource>, Error> {
+    let mono = |seed| -> Result<BoxedSource, Error> {
+        let source = mono_source(args, sample_rate, seed)?;
+        let source: BoxedSource = if let Some(target) = args.crossfade_to {
+            let gain = args.loudness(target);
+            let target = args.to_noise(
+                target,
+                sample_rate,
+                SipHasher13::new_with_key(&seed)
+                    .hash(b"crossfade")
+                    .as_bytes(),
+            )?;
+            let target = target.amplify(gain);
+            Box::new(CrossfadeNoise::new(source, target, args.crossfade_duration))
+        } else {
+            source
+        };
+        // Switching the noise type replaces a running crossfade, too.
+        let slot = Arc::<Slot>::default();
+        shared.switches.borrow_mut().push((Arc::clone(&slot), seed));
+        let source = blend_tone(args, sample_rate, Box::new(Switch::new(source, slot)))?;
+        band_limit(args, sample_rate, source)
+    };
+    let mut source: BoxedSource = if let Some([base, beat]) = args.binaural.as_deref() {
+        binaural(sample_rate, *base, *beat)?
+    } else if args.stereo {
+        let left = mono(channel_seed(seed, 0))?;
+        let right = mono(channel_seed(seed, 1))?;
+        Box::new(Interleave::new(vec![left, right]))
     } else {
-        return Err(Error::Amplitude(args.amplitude));
+        let noise = mono(seed)?;
+        match channels {
+            1 => Box::new(noise),
+            _ => Box::new(Replicate::new(noise, channels)),
+        }
+    };
+    shared.sample_rate.set(sample_rate);
+    // The sources of all devices share the filters, so a reload retunes all of them.
+    if shared.filters.borrow().is_empty() {
+        let filters = filter_coefficients(args, sample_rate)?
+            .into_iter()
+            .map(|coefficients| Arc::new(Tunable::new(coefficients)));
+        *shared.filters.borrow_mut() = filters.collect();
+    }
+    for tunable in shared.filters.borrow().iter() {
+        source = Box::new(Biquad::new(source, Arc::clone(tunable)));
+    }
+    if args.compress {
+        let settings = compressor::Settings {
+            threshold_db: args.threshold_db,
+            ratio: args.ratio,
+            attack_ms: args.attack_ms,
+            release_ms: args.release_ms,
+        };
+        source = Box::new(Compressor::new(source, settings)?);
+    }
+    if let Some(width) = args.width {
+        source = Box::new(Width::new(source, width)?);
+    }
+    if let Some(amount) = args.crossfeed {
+        source = Box::new(Crossfeed::new(source, amount)?);
+    }
+    if let Some(pan) = args.pan {
+        shared.pan.store(check_pan(pan)?.to_bits(), Relaxed);
+        match source.channels() {
+            1 => {}
+            2 => source = Box::new(Pan::new(source, Arc::clone(&shared.pan))),
+            channels => return Err(Error::PanChannels(channels)),
+        }
+    } else if let Some(rate) = args.auto_pan_rate {
+        if !(rate > 0.0 && rate <= MAX_AUTO_PAN_RATE) {
+            return Err(Error::AutoPanRate(rate));
+        }
+        match source.channels() {
+            1 => {}
+            2 => source = Box::new(Pan::sweep(source, rate)),
+            channels => return Err(Error::PanChannels(channels)),
+        }
+    }
+    if let Some(delay) = args.haas {
+        source = Box::new(Haas::new(source, delay)?);
+    }
+    let floor = match args.floor {
+        Some(floor) => {
+            let floor = noisy_silence::check_amplitude(floor).map_err(Error::Noise)?;
+            let dry = Arc::new(AtomicU32::new(0));
+            source = Box::new(Tap::new(source, Arc::clone(&dry)));
+            Some((floor, dry))
+        }
+        None => None,
     };
-    let stream = rodio::OutputStreamBuilder::open_default_stream()?;
-    let sample_rate = stream.config().sample_rate();
-    let noise = args.noise.to_noise(sample_rate).amplify(amplitude * 0.01);
-    stream.mixer().add(noise);
+    if args.lfo_depth != 0.0 {
+        if !(0.0..=1.0).contains(&args.lfo_depth) {
+            return Err(Error::LfoDepth(args.lfo_depth));
+        }
+        if !(args.lfo_rate > 0.0 && args.lfo_rate <= MAX_LFO_RATE) {
+            return Err(Error::LfoRate(args.lfo_rate));
+        }
+        source = Box::new(Tremolo::new(source, args.lfo_rate, args.lfo_depth));
+    }
+    if let Some(duration) = args.ramp_up {
+        let curve = args.ramp_curve.unwrap_or(args.fade_curve);
+        source = Box::new(RampUp::new(source, duration, curve));
+    }
+    let balance = channel_balance(args, source.channels())?;
+    source = Box::new(Volume::new(source, Arc::clone(&shared.gain), balance));
+    source = Box::new(Headroom::new(source, Arc::clone(&shared.counters)));
+    if !args.no_limiter {
+        source = Box::new(Limiter::new(source, Arc::clone(&shared.counters)));
+    }
+    if let (Some(on), Some(off)) = (args.on, args.off) {
+        source = Box::new(DutyCycle::new(source, on, off, spawn_phase_log()?)?);
+    } else if args.intermittent {
+        if args.on_range.min.is_zero() {
+            return Err(Error::DutyCycle);
+        }
+        let rng = args.rng.to_rng(
+            SipHasher13::new_with_key(&seed)
+                .hash(b"intermittent")
+                .as_bytes(),
+        );
+        source = Box::new(Intermittent::new(
+            source,
+            args.on_range,
+            args.off_range,
+            rng,
+            spawn_phase_log()?,
+        ));
+    }
+    // Never start at the full amplitude, even if the user asked for a shorter fade-in.
+    source = Box::new(RampUp::new(
+        source,
+        args.fade_in.max(MIN_FADE_IN),
+        args.fade_curve,
+    ));
+    if let Some((floor, dry)) = floor {
+        source = Box::new(Floor::new(source, floor, dry));
+    }
+    if let Some(duration) = args.duration {
+        source = Box::new(Timed::new(source, duration, tx.clone()));
+    }
+    if let Some(delay) = args.delay {
+        source = Box::new(source.delay(delay));
+    }
+    Ok(FadeOut::new(
+        source,
+        args.fade_out,
+        args.fade_curve,
+        Arc::clone(&shared.fading),
+        tx,
+    ))
+}
+
+/// The `--channel-amplitudes` relative to the loudest channel, empty if they are not set
+fn channel_balance(args: &Args, channels: ChannelCount) -> Result<Vec<f32>, Error> {
+    if args.channel_amplitudes.is_empty() {
+        return Ok(Vec::new());
+    }
+    if args.channel_amplitudes.len() != usize::from(channels) {
+        return Err(Error::ChannelAmplitudes(
+            args.channel_amplitudes.len(),
+            channels,
+        ));
+    }
+    let loudest = amplitude(args)?;
+    Ok(args
+        .channel_amplitudes
+        .iter()
+        .map(|amplitude| amplitude / loudest)
+        .collect())
+}
+
+/// Layer the `--blend-tone` sine tone under the mono `noise`, at its level relative to the full
+/// scale of the noise
+fn blend_tone(
+    args: &Args,
+    sample_rate: SampleRate,
+    noise: BoxedSource,
+) -> Result<BoxedSource, Error> {
+    let Some(frequency) = args.blend_tone else {
+        return Ok(noise);
+    };
+    let level = args.blend_tone_level;
+    if !(MIN_BLEND_TONE_LEVEL..=0.0).contains(&level) {
+        return Err(Error::BlendToneLevel(level));
+    }
+    let frequency = check_frequency(sample_rate, frequency)?;
+    let tone: BoxedSource = Box::new(SignalGenerator::new(sample_rate, frequency, Function::Sine));
+    let gain = 10f32.powf(level / 20.0);
+    Ok(Box::new(MixedNoise::new(vec![(noise, 1.0), (tone, gain)])))
+}
+
+/// Apply the steep `--bandlimit` low-pass filter to the generated mono `source`
+fn band_limit(
+    args: &Args,
+    sample_rate: SampleRate,
+    mut source: BoxedSource,
+) -> Result<BoxedSource, Error> {
+    if let Some(cutoff) = args.bandlimit {
+        let cutoff = check_frequency(sample_rate, cutoff)?;
+        for coefficients in Coefficients::band_limit(sample_rate, cutoff) {
+            source = Box::new(Biquad::new(source, Arc::new(Tunable::new(coefficients))));
+        }
+    }
+    Ok(source)
+}
+
+/// The configured mono tone or noise generator, with the loudness compensation applied
+fn mono_source(args: &Args, sample_rate: SampleRate, seed: [u8; 16]) -> Result<BoxedSource, Error> {
+    if args.silent {
+        return Ok(Box::new(Zero::new(1, sample_rate)));
+    }
+    if let Some(frequency) = args.tone {
+        let frequency = check_frequency(sample_rate, frequency)?;
+        let tone = SignalGenerator::new(sample_rate, frequency, Function::Sine);
+        return Ok(Box::new(tone));
+    }
+    if let Some(alpha) = args.slope {
+        let noise = SlopeNoise::with_rng(sample_rate, alpha, args.rng.to_rng(seed))
+            .map_err(Error::Noise)?;
+        return Ok(Box::new(noise));
+    }
+    if args.mix.is_empty() {
+        let noise = args.to_noise(args.noise, sample_rate, seed)?;
+        return Ok(Box::new(noise.amplify(args.loudness(args.noise))));
+    }
+    let total: f32 = args.mix.iter().map(|item| item.weight).sum();
+    if !(total.is_finite() && total > 0.0) {
+        return Err(Error::MixWeights);
+    }
+    let inputs = args.mix.iter().enumerate().map(|(index, item)| {
+        let seed = match index {
+            0 => seed,
+            _ => SipHasher13::new_with_key(&seed)
+                .hash(format!("mix {index}").as_bytes())
+                .as_bytes(),
+        };
+        let weight = item.weight / total * args.loudness(item.noise);
+        Ok((args.to_noise(item.noise, sample_rate, seed)?, weight))
+    });
+    Ok(Box::new(MixedNoise::new(
+        inputs.collect::<Result<_, Error>>()?,
+    )))
+}
+
+/// A stereo source with a sine tone of `base` Hz on the left and `base + beat` Hz on the right
+fn binaural(sample_rate: SampleRate, base: f32, beat: f32) -> Result<BoxedSource, Error> {
+    if !beat.is_finite() || beat <= 0.0 {
+        return Err(Error::Beat(beat));
+    }
+    let tone = |frequency| -> Result<BoxedSource, Error> {
+        let frequency = check_frequency(sample_rate, frequency)?;
+        Ok(Box::new(SignalGenerator::new(
+            sample_rate,
+            frequency,
+            Function::Sine,
+        )))
+    };
+    Ok(Box::new(Interleave::new(vec![
+        tone(base)?,
+        tone(base + beat)?,
+    ])))
+}
 
+/// The coefficients of the requested filters, in the order in which they are applied
+fn filter_coefficients(args: &Args, sample_rate: SampleRate) -> Result<Vec<Coefficients>, Error> {
+    let mut filters = Vec::new();
+    if args.a_weight {
+        let sections =
+            Coefficients::a_weighting(sample_rate).ok_or(Error::AWeightSampleRate(sample_rate))?;
+        filters.extend(sections);
+    }
+    if let Some(cutoff) = args.lowpass {
+        let cutoff = check_frequency(sample_rate, cutoff)?;
+        filters.push(Coefficients::low_pass(sample_rate, cutoff));
+    }
+    if let Some(cutoff) = args.highpass {
+        let cutoff = check_frequency(sample_rate, cutoff)?;
+        filters.push(Coefficients::high_pass(sample_rate, cutoff));
+    }
+    if let Some(center) = args.bandpass {
+        let center = check_frequency(sample_rate, center)?;
+        let q = check_q(args.q)?;
+        filters.push(Coefficients::band_pass(sample_rate, center, q));
+    }
+    if let Some(center) = args.notch {
+        let center = check_frequency(sample_rate, center)?;
+        let q = check_q(args.notch_q)?;
+        filters.push(Coefficients::notch(sample_rate, center, q));
+    }
+    for band in &args.eq {
+        let center = check_frequency(sample_rate, band.frequency)?;
+        filters.push(Coefficients::peaking(
+            sample_rate,
+            center,
+            band.gain_db,
+            band.q,
+        ));
+    }
+    Ok(filters)
+}
+
+fn check_pan(pan: f32) -> Result<f32, Error> {
+    if (-1.0..=1.0).contains(&pan) {
+        Ok(pan)
+    } else {
+        Err(Error::Pan(pan))
+    }
+}
+
+/// Write `source` into the `--output` file, or to stdout
+fn write_output(
+    args: &Args,
+    path: &Path,
+    source: impl Source,
+    amplitude: f32,
+    seed: [u8; 16],
+    fading: &AtomicBool,
+    rx: &mpsc::Receiver<Event>,
+) -> Result<(), Error> {
+    if path == Path::new("-") {
+        let quantizer = quantizer(args, SampleFormat::F32, seed);
+        info!(
+            "Writing {} with an amplitude of {amplitude:.2}% to stdout.",
+            args.sound(),
+        );
+        return write_raw(
+            source,
+            quantizer,
+            args.output_format,
+            fading,
+            rx,
+            args.quiet,
+        );
+    }
+    if args.duration.is_none() {
+        return Err(Error::OutputDuration);
+    }
+    let quantizer = quantizer(args, SampleFormat::S16, seed);
     info!(
-        "Now playing {} noise with an amplitude of {amplitude:.2}%.",
-        args.noise
+        "Writing {} with an amplitude of {amplitude:.2}% to {}.",
+        args.sound(),
+        path.display(),
     );
-    eprintln!("Press ctrl+C to end the process.");
+    write_wav(path, source, quantizer, fading, rx, args.quiet)
+}
 
-    let _: Result<(), mpsc::RecvError> = rx.recv();
-    eprintln!();
-    info!("Closing stream and exiting.");
-    drop(stream);
+/// Pull the first samples through the whole chain without playing them, and log their levels
+fn dry_run(args: &Args, source: impl Source, counters: &metrics::Counters) {
+    let frames = DRY_RUN_DURATION.as_secs() * u64::from(source.sample_rate());
+    let samples = frames * u64::from(source.channels());
+    let (mut peak, mut sum, mut count, mut invalid) = (0f32, 0f64, 0u64, 0u64);
+    #[expect(clippy::cast_possible_truncation)]
+    for sample in source.take(samples as usize) {
+        if !sample.is_finite() {
+            invalid += 1;
+            continue;
+        }
+        peak = peak.max(sample.abs());
+        sum += f64::from(sample * sample);
+        count += 1;
+    }
+    #[expect(clippy::cast_precision_loss)]
+    let rms = if count > 0 {
+        (sum / count as f64).sqrt()
+    } else {
+        0.0
+    };
+    info!(
+        "Generated {count} samples of {}: peak {:.1} dBFS, RMS {:.1} dBFS, {} samples limited.",
+        args.sound(),
+        noisy_silence::dbfs(f64::from(peak)),
+        noisy_silence::dbfs(rms),
+        counters.limited.load(Relaxed),
+    );
+    if invalid > 0 {
+        warn!("{invalid} samples were not finite numbers.");
+    }
+}
+
+/// The quantizer for the requested `--bit-depth` and `--dither`
+fn quantizer(args: &Args, default_format: SampleFormat, seed: [u8; 16]) -> Quantizer {
+    let format = args.bit_depth.unwrap_or(default_format);
+    Quantizer::new(format, dither(args), seed)
+}
+
+/// The requested `--dither`
+fn dither(args: &Args) -> Option<Dither> {
+    // Dither would turn the silence into noise.
+    args.dither.or(args.silent.then_some(Dither::None))
+}
+
+/// The format of a WAV file with samples of the quantizer's `format`
+fn wav_spec(
+    channels: ChannelCount,
+    sample_rate: SampleRate,
+    format: SampleFormat,
+) -> hound::WavSpec {
+    hound::WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: format.bits(),
+        sample_format: match format {
+            SampleFormat::F32 => hound::SampleFormat::Float,
+            _ => hound::SampleFormat::Int,
+        },
+    }
+}
+
+/// Write `source` into a WAV file until it ends, or until the user presses ctrl+C
+fn write_wav(
+    path: &Path,
+    source: impl Source,
+    mut quantizer: Quantizer,
+    fading: &AtomicBool,
+    rx: &mpsc::Receiver<Event>,
+    quiet: bool,
+) -> Result<(), Error> {
+    let spec = wav_spec(source.channels(), source.sample_rate(), quantizer.format());
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    write_samples(source, fading, rx, quiet, |sample| {
+        match quantizer.format() {
+            SampleFormat::F32 => writer.write_sample(sample)?,
+            _ => writer.write_sample(quantizer.quantize(sample))?,
+        }
+        Ok(true)
+    })?;
+    writer.finalize()?;
+    info!("Done writing {}.", path.display());
+    Ok(())
+}
+
+/// Write `source` to stdout as raw samples until it ends, until the reader closes the pipe, or
+/// until the user presses ctrl+C
+///
+/// With [`OutputFormat::Wav`] the samples are preceded by a WAV header.
+fn write_raw(
+    source: impl Source,
+    mut quantizer: Quantizer,
+    format: OutputFormat,
+    fading: &AtomicBool,
+    rx: &mpsc::Receiver<Event>,
+    quiet: bool,
+) -> Result<(), Error> {
+    let mut out = BufWriter::new(stdout().lock());
+    if format == OutputFormat::Wav {
+        let spec = wav_spec(source.channels(), source.sample_rate(), quantizer.format());
+        match write_wav_header(&mut out, spec) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::BrokenPipe => return Ok(()),
+            Err(err) => return Err(Error::Stdout(err)),
+        }
+    }
+    let result = write_samples(source, fading, rx, quiet, |sample| {
+        match quantizer.write_le(&mut out, sample) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == ErrorKind::BrokenPipe => Ok(false),
+            Err(err) => Err(Error::Stdout(err)),
+        }
+    });
+    match out.flush() {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::BrokenPipe => {}
+        Err(err) => return Err(Error::Stdout(err)),
+    }
+    result
+}
+
+/// Write the header of a WAV stream of unknown length
+///
+/// The stream never ends before the reader stops reading, so the sizes of the RIFF and the data
+/// chunk are set to their maximum, 0xFFFF_FFFF, which players treat as "until the end of the
+/// stream".
+fn write_wav_header(out: &mut impl Write, spec: hound::WavSpec) -> std::io::Result<()> {
+    let format_tag: u16 = match spec.sample_format {
+        hound::SampleFormat::Int => 1,
+        hound::SampleFormat::Float => 3,
+    };
+    let block_align = spec.channels * (spec.bits_per_sample / 8);
+    let byte_rate = spec.sample_rate * u32::from(block_align);
+    out.write_all(b"RIFF")?;
+    out.write_all(&u32::MAX.to_le_bytes())?;
+    out.write_all(b"WAVEfmt ")?;
+    out.write_all(&16u32.to_le_bytes())?;
+    out.write_all(&format_tag.to_le_bytes())?;
+    out.write_all(&spec.channels.to_le_bytes())?;
+    out.write_all(&spec.sample_rate.to_le_bytes())?;
+    out.write_all(&byte_rate.to_le_bytes())?;
+    out.write_all(&block_align.to_le_bytes())?;
+    out.write_all(&spec.bits_per_sample.to_le_bytes())?;
+    out.write_all(b"data")?;
+    out.write_all(&u32::MAX.to_le_bytes())
+}
+
+/// Pass every sample of `source` to `write` until it ends, or until `write` returns `false`
+fn write_samples(
+    source: impl Source,
+    fading: &AtomicBool,
+    rx: &mpsc::Receiver<Event>,
+    quiet: bool,
+    mut write: impl FnMut(Sample) -> Result<bool, Error>,
+) -> Result<(), Error> {
+    for (index, sample) in source.enumerate() {
+        if index % 4096 == 0 && matches!(rx.try_recv(), Ok(Event::Cancelled)) {
+            if !quiet {
+                eprintln!();
+            }
+            info!("Stopping early.");
+            fading.store(true, Relaxed);
+        }
+        if !write(sample)? {
+            break;
+        }
+    }
     Ok(())
 }
 
 /// Output a continuous stream of (almost) silence.
-#[derive(Debug, Parser)]
-#[command(version, about, long_about = long_about())]
+#[derive(Debug, Clone, Parser)]
+#[command(version, long_version = LONG_VERSION, about, long_about = long_about())]
+#[expect(clippy::struct_excessive_bools)]
 struct Args {
     /// The type of noise to play
     #[arg(default_value_t = NoiseValue::default(), value_enum)]
@@ -81,74 +1227,876 @@ struct Args {
     /// The output amplitude in percent
     #[arg(default_value_t = 0.1)]
     amplitude: f32,
+    /// The output amplitude in dBFS, e.g. "-40", instead of a percentage
+    #[arg(
+        short,
+        long,
+        conflicts_with = "amplitude",
+        allow_negative_numbers = true
+    )]
+    amplitude_db: Option<f32>,
+    /// A separate output amplitude in percent for every channel, e.g. "0.1,0.08", instead of a
+    /// single amplitude; the number of values must match the number of channels
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        value_delimiter = ',',
+        conflicts_with_all = ["amplitude", "amplitude_db"],
+    )]
+    channel_amplitudes: Vec<f32>,
+    /// The output amplitude in percent while the system runs on battery power
+    #[arg(long, value_name = "PERCENT")]
+    battery_amplitude: Option<f32>,
+    /// Let the amplitude follow the breakpoints in this file, one "<SECONDS>,<PERCENT>" per line,
+    /// linearly interpolated
+    #[arg(long, value_name = "PATH", conflicts_with = "battery_amplitude")]
+    envelope: Option<PathBuf>,
+    /// Never let the output amplitude drop below this percentage, not even during fades, LFO
+    /// troughs, or duty-cycle pauses; the exit fade-out still ends in silence
+    #[arg(long, value_name = "PERCENT", conflicts_with = "silent")]
+    floor: Option<f32>,
+    /// Mix multiple noise types with the given weights instead of playing a single type, e.g.
+    /// "brownian:0.7,pink:0.3"
+    #[arg(short, long, value_delimiter = ',', value_parser = parse_mix_item)]
+    mix: Vec<MixItem>,
+    /// Play noise with a power spectral density of 1/f^SLOPE instead of the selected type, where 0
+    /// is white, 1 is pink, and 2 is brownian noise
+    #[arg(long, conflicts_with = "mix")]
+    slope: Option<f32>,
+    /// Play a sine tone with this frequency in Hz instead of noise, e.g. for calibration
+    #[arg(long, value_name = "HZ", conflicts_with_all = ["mix", "slope", "crossfade_to"])]
+    tone: Option<f32>,
+    /// Layer a quiet sine tone with this frequency in Hz under the noise, e.g. for masking
+    #[arg(
+        long,
+        value_name = "HZ",
+        conflicts_with_all = ["tone", "binaural"],
+    )]
+    blend_tone: Option<f32>,
+    /// The level of the `--blend-tone` in dB relative to the full scale of the noise, between -80
+    /// and 0; the amplitude and the limiter apply to the sum
+    #[arg(
+        long,
+        value_name = "DB",
+        default_value_t = -20.0,
+        allow_negative_numbers = true,
+        requires = "blend_tone",
+    )]
+    blend_tone_level: f32,
+    /// Play exact digital silence instead of noise, e.g. as a baseline to debug the signal chain
+    #[arg(
+        long,
+        conflicts_with_all = ["mix", "slope", "tone", "blend_tone", "binaural", "crossfade_to"],
+    )]
+    silent: bool,
+    /// Play a sine tone with `BASE_HZ` on the left and `BASE_HZ + BEAT_HZ` on the right channel
+    /// instead of noise, which you hear as a slow binaural beat with headphones
+    #[arg(
+        long,
+        num_args = 2,
+        allow_negative_numbers = true,
+        value_names = ["BASE_HZ", "BEAT_HZ"],
+        conflicts_with_all = ["mix", "slope", "tone", "crossfade_to", "stereo", "channels"],
+    )]
+    binaural: Option<Vec<f32>>,
+    /// The standard deviation of gaussian noise relative to the full scale, clamped to 0.01 to 1
+    /// [default: 0.333]; the generator cuts off the samples at 3 standard deviations, and the
+    /// amplitude and the limiter apply afterwards
+    #[arg(long, value_name = "STDDEV")]
+    gaussian_stddev: Option<f32>,
+    /// The half width of the distribution of triangular noise relative to the full scale
+    /// [default: 1]; the peaks reach this width times the amplitude, and the limiter catches the
+    /// samples above the full scale
+    #[arg(long, value_name = "WIDTH", value_parser = parse_positive)]
+    triangular_width: Option<f32>,
+    /// The fraction of its value that the integrator of brownian noise loses every sample, between
+    /// 0 and 1; more leak means less wander in the low end [default: about 0.00065 at 48 kHz, a
+    /// corner frequency of 5 Hz]
+    #[arg(long, value_name = "LEAK")]
+    brownian_leak: Option<f32>,
+    /// The number of impulses per second of velvet noise [default: 2000]
+    #[arg(long, value_name = "PER_SEC")]
+    velvet_density: Option<f32>,
+    /// The algorithm that generates pink noise
+    #[arg(long, value_enum, default_value_t)]
+    pink_algo: PinkAlgo,
+    /// The random number generator of the noise
+    #[arg(long, value_enum, default_value_t)]
+    rng: RngValue,
+    /// Slowly crossfade into this noise type
+    #[arg(long, value_enum)]
+    crossfade_to: Option<NoiseValue>,
+    /// The duration of the crossfade
+    #[arg(
+        long,
+        default_value = "10m",
+        value_parser = humantime::parse_duration,
+        requires = "crossfade_to"
+    )]
+    crossfade_duration: Duration,
+    /// Switch to the next noise type of `--rotate` with a short crossfade this often, e.g. "30m"
+    #[arg(
+        long,
+        value_parser = humantime::parse_duration,
+        conflicts_with_all = ["mix", "slope", "tone", "binaural", "silent"]
+    )]
+    rotate_every: Option<Duration>,
+    /// The noise types to rotate through, e.g. "brownian,pink", defaults to all of them
+    #[arg(long, value_enum, value_delimiter = ',', requires = "rotate_every")]
+    rotate: Vec<NoiseValue>,
+    /// Do not adjust the amplitude of each noise type so that they all sound about equally loud
+    #[arg(long)]
+    no_loudness_compensation: bool,
+    /// Do not clamp the samples to the full scale, i.e. let loud mixes clip
+    #[arg(long)]
+    no_limiter: bool,
+    /// Play independent noise on the left and the right channel
+    #[arg(short = 'S', long, conflicts_with = "channels")]
+    stereo: bool,
+    /// Play the noise on this many channels, defaults to the channels of the output device
+    #[arg(short, long, value_parser = clap::value_parser!(ChannelCount).range(1..))]
+    channels: Option<ChannelCount>,
+    /// Move the noise between the left (-1) and the right (1) channel of a stereo output
+    #[arg(long, allow_negative_numbers = true)]
+    pan: Option<f32>,
+    /// Slowly move the noise from side to side of a stereo output this many times per second, up
+    /// to 0.2
+    #[arg(long, value_name = "HZ", conflicts_with = "pan")]
+    auto_pan_rate: Option<f32>,
+    /// The stereo width, from 0 for mono over 1 for the unchanged channels to 2 for an exaggerated
+    /// difference between them, e.g. with `--stereo`
+    #[arg(long, value_name = "0..2")]
+    width: Option<f32>,
+    /// Mix this amount, from 0 to 1, of each stereo channel low-passed and delayed into the other
+    /// one, so the noise sounds less wide on headphones
+    #[arg(long, value_name = "0..1")]
+    crossfeed: Option<f32>,
+    /// Delay the right channel of a stereo output by this many milliseconds, up to 40, to make the
+    /// noise sound wider
+    #[arg(long, value_name = "MS")]
+    haas: Option<f32>,
+    /// Generate the noise without content above this frequency in Hz, with a steep eighth order
+    /// low-pass filter, e.g. to resample it cleanly later
+    #[arg(long, value_name = "HZ")]
+    bandlimit: Option<f32>,
+    /// Apply a low-pass filter with this cutoff frequency in Hz
+    #[arg(long)]
+    lowpass: Option<f32>,
+    /// Apply a high-pass filter with this cutoff frequency in Hz, combine with `--lowpass` to get
+    /// a band-pass
+    #[arg(long)]
+    highpass: Option<f32>,
+    /// Weight the output with the A-weighting curve of IEC 61672, like a sound level meter, needs a
+    /// sample rate of at least 24.4 kHz
+    #[arg(long)]
+    a_weight: bool,
+    /// Apply a band-pass filter with this center frequency in Hz
+    #[arg(long)]
+    bandpass: Option<f32>,
+    /// The quality factor of the band-pass filter, higher values make the band narrower
+    #[arg(long, default_value_t = 0.707, requires = "bandpass")]
+    q: f32,
+    /// Apply a notch filter to suppress this frequency in Hz
+    #[arg(long)]
+    notch: Option<f32>,
+    /// The quality factor of the notch filter, higher values make the notch narrower
+    #[arg(long, default_value_t = 5.0, requires = "notch")]
+    notch_q: f32,
+    /// Apply up to 8 peaking EQ bands, each given as "FREQUENCY:GAIN_DB:Q", e.g.
+    /// "100:3:0.7,3500:-6:2"
+    #[arg(long, value_delimiter = ',', value_parser = parse_eq_band, allow_negative_numbers = true)]
+    eq: Vec<EqBand>,
+    /// Compress the dynamics of the noise, e.g. to glue a mix with a tone together
+    #[arg(long)]
+    compress: bool,
+    /// The level in dBFS above which the compressor reduces the gain
+    #[arg(
+        long,
+        default_value_t = 0.0,
+        requires = "compress",
+        allow_negative_numbers = true
+    )]
+    threshold_db: f32,
+    /// How many dB the input must rise above the threshold to raise the output by one dB
+    #[arg(long, default_value_t = 2.0, requires = "compress")]
+    ratio: f32,
+    /// How fast the compressor reduces the gain in milliseconds
+    #[arg(long, default_value_t = 20.0, requires = "compress")]
+    attack_ms: f32,
+    /// How fast the gain recovers after the level fell in milliseconds
+    #[arg(long, default_value_t = 250.0, requires = "compress")]
+    release_ms: f32,
+    /// How often per second the amplitude swells, requires `--lfo-depth`
+    #[arg(long, default_value_t = 0.1, requires = "lfo_depth")]
+    lfo_rate: f32,
+    /// How much the amplitude swells between 0 (not at all) and 1 (down to silence)
+    #[arg(long, default_value_t = 0.0)]
+    lfo_depth: f32,
+    /// Play the noise in bursts of this duration, requires `--off`
+    #[arg(long, value_parser = humantime::parse_duration, requires = "off")]
+    on: Option<Duration>,
+    /// Be silent for this duration between the bursts, requires `--on`
+    #[arg(long, value_parser = humantime::parse_duration, requires = "on")]
+    off: Option<Duration>,
+    /// Play the noise in bursts of random durations, with random pauses in between, see
+    /// `--on-range` and `--off-range`
+    #[arg(long, conflicts_with_all = ["on", "off"])]
+    intermittent: bool,
+    /// The shortest and the longest burst of `--intermittent`, e.g. "3m-7m"
+    #[arg(
+        long,
+        value_name = "MIN-MAX",
+        default_value = "3m-7m",
+        value_parser = parse_duration_range,
+        requires = "intermittent",
+    )]
+    on_range: DurationRange,
+    /// The shortest and the longest pause of `--intermittent`, e.g. "5m-15m"
+    #[arg(
+        long,
+        value_name = "MIN-MAX",
+        default_value = "5m-15m",
+        value_parser = parse_duration_range,
+        requires = "intermittent",
+    )]
+    off_range: DurationRange,
+    /// Stop playing after the given duration, e.g. "30m" or "2h"
+    #[arg(short, long, value_parser = humantime::parse_duration)]
+    duration: Option<Duration>,
+    /// Stay silent for this duration before the noise begins, e.g. "10m"
+    #[arg(long, value_parser = humantime::parse_duration)]
+    delay: Option<Duration>,
+    /// Fade in the noise over this duration to prevent a pop and to protect your ears, it always
+    /// takes at least 250ms
+    #[arg(long, default_value = "250ms", value_parser = humantime::parse_duration)]
+    fade_in: Duration,
+    /// Slowly raise the amplitude from silence over this duration, e.g. "15m"
+    #[arg(long, value_parser = humantime::parse_duration)]
+    ramp_up: Option<Duration>,
+    /// How the amplitude rises during `--ramp-up` [default: the `--fade-curve`]
+    #[arg(long, value_enum, requires = "ramp_up")]
+    ramp_curve: Option<FadeCurve>,
+    /// Fade out the noise over this duration before exiting, press ctrl+C again to skip the fade
+    #[arg(long, default_value = "50ms", value_parser = humantime::parse_duration)]
+    fade_out: Duration,
+    /// The shape of the fade-in, the fade-out, and the `--ramp-up`
+    #[arg(long, value_enum, default_value_t)]
+    fade_curve: FadeCurve,
+    /// What the first ctrl+C does
+    #[arg(long, value_enum, default_value_t)]
+    ctrlc_mode: CtrlcMode,
+    /// Derive the random number generator's seed from this phrase, the same phrase always yields
+    /// the same noise
+    #[arg(short, long, alias = "seed-phrase")]
+    seed: Option<String>,
+    /// Use a random seed instead of the default one
+    #[arg(short, long, conflicts_with = "seed")]
+    random_seed: bool,
+    /// Write the noise into this WAV file instead of playing it, requires `--duration` unless
+    /// `--tee` is given; "-" writes raw interleaved little-endian samples to stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Play the noise, too, while recording it into the `--output` WAV file until the playback
+    /// ends
+    #[arg(long, requires = "output")]
+    tee: bool,
+    /// Do not play the noise, but generate its first 10 seconds to check the settings, and log
+    /// their levels
+    #[arg(long, env = "NOISY_NO_AUDIO", value_parser = clap::builder::FalseyValueParser::new())]
+    dry_run: bool,
+    /// The format of `--output -`: raw samples, or a WAV stream that players can read from a
+    /// pipe
+    #[arg(long, value_enum, default_value_t, requires = "output")]
+    output_format: OutputFormat,
+    /// The bit depth of the samples in `--output`, "32f" for floats [default: 16 for WAV files,
+    /// 32f for stdout]
+    #[arg(long, value_enum, requires = "output")]
+    bit_depth: Option<SampleFormat>,
+    /// Add this dither before rounding to an integer `--bit-depth` or `--sample-format`
+    /// [default: tpdf]
+    #[arg(long, value_enum)]
+    dither: Option<Dither>,
+    /// Play the noise with this sample format, it is an error if the output device does not
+    /// support it [default: the format of the device, converted from f32]
+    #[arg(long, value_enum)]
+    sample_format: Option<StreamFormat>,
+    /// Ask the output device for a buffer of this many frames, e.g. "256" for a low latency, or
+    /// "4096" to avoid underruns on a slow system [default: the buffer size of the device]
+    #[arg(long, value_name = "FRAMES", value_parser = clap::value_parser!(u32).range(1..))]
+    buffer_frames: Option<u32>,
+    /// The sample rate in Hz, defaults to the rate of the output device
+    #[arg(long, value_parser = clap::value_parser!(SampleRate).range(8_000..=384_000))]
+    sample_rate: Option<SampleRate>,
+    /// Play on the output device whose name contains this string; with a comma-separated list,
+    /// e.g. "USB,HDMI", the first device that can be opened is used
+    #[arg(short = 'D', long, value_delimiter = ',')]
+    device: Vec<String>,
+    /// Play on all devices of the `--device` list at the same time, instead of the first one that
+    /// can be opened; every device plays the same noise, unless `--decorrelate-devices` is given
+    #[arg(long, requires = "device")]
+    all_devices: bool,
+    /// Play independent noise on every device of `--all-devices`, with seeds derived from the seed
+    #[arg(long, requires = "all_devices")]
+    decorrelate_devices: bool,
+    /// List the available output devices, the default device is marked with a '*'
+    #[arg(short, long)]
+    list_devices: bool,
+    /// How often to try to reconnect if the output device is lost, e.g. because it was unplugged
+    #[arg(long, default_value_t = 5)]
+    reconnect_retries: u32,
+    /// Pause the noise while the keyboard and the mouse were not used for this duration, e.g.
+    /// "10min", and resume it on the next activity
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    pause_after_idle: Option<Duration>,
+    /// Restart the audio stream if it did not play any samples for this duration, e.g. "5s"
+    #[arg(long, value_parser = humantime::parse_duration)]
+    watchdog_timeout: Option<Duration>,
+    /// Listen for OSC messages on this UDP address, e.g. "0.0.0.0:9000", to change the amplitude
+    /// with "/amplitude <PERCENT>" and the noise type with "/noise <NOISE>"
+    #[arg(long, value_name = "HOST:PORT")]
+    osc_listen: Option<String>,
+    /// Serve Prometheus metrics over HTTP on this address, e.g. "127.0.0.1:9100"
+    #[arg(long, value_name = "HOST:PORT")]
+    metrics_addr: Option<String>,
+    /// Read default settings from this TOML file, instead of "noisy-silence/config.toml" in the
+    /// user's config directory
+    #[arg(short = 'C', long)]
+    config: Option<PathBuf>,
+    /// Use the settings of this preset, e.g. "sleep", instead of the ones in the config file;
+    /// options given on the command line take precedence
+    #[arg(short = 'P', long)]
+    preset: Option<String>,
+    /// Remember the noise type and the amplitude when the program exits, and start with them next
+    /// time; options given on the command line and in the config file take precedence
+    #[arg(long)]
+    remember: bool,
+    /// List the noise types, and describe how they sound
+    #[arg(long)]
+    list_noise_types: bool,
+    /// List the built-in presets, and the ones in the `[presets]` table of the config file
+    #[arg(long)]
+    list_presets: bool,
+    /// Measure the spectrum of this noise type after the configured filters, or of the
+    /// `--slope` noise, print its level per octave and its slope, and exit
+    #[arg(long, value_name = "NOISE", value_enum)]
+    info: Option<NoiseValue>,
+    /// Only print warnings and errors
+    #[arg(short, long)]
+    quiet: bool,
+    /// The audio backend to play the noise with
+    #[arg(long, value_enum, default_value_t)]
+    backend: Backend,
+    /// The format of the log messages
+    #[arg(long, value_enum, default_value_t, env = "NOISY_LOG_FORMAT")]
+    log_format: LogFormat,
+    /// When to color the log messages, "auto" colors them if stderr is a terminal, and `NO_COLOR`
+    /// is not set
+    #[arg(long, value_enum, default_value_t)]
+    color: Color,
+    /// Display the peak and RMS level of the noise while playing
+    #[arg(long, conflicts_with = "output")]
+    meter: bool,
+    /// Change the amplitude with the keyboard while playing
+    #[arg(short, long, conflicts_with = "output")]
+    interactive: bool,
     /// Print the license text
     #[arg(short = 'L', long)]
     license: bool,
+    /// Print a completion script for this shell
+    #[arg(long, value_name = "SHELL")]
+    completions: Option<clap_complete::Shell>,
 }
 
-nodyn::nodyn! {
-    enum Noise {
-        White(noise::WhiteUniform<Xoroshiro128Plus>),
-        Gaussian(noise::WhiteGaussian<Xoroshiro128Plus>),
-        Triangular(noise::WhiteTriangular<Xoroshiro128Plus>),
-        Pink(noise::Pink<Xoroshiro128Plus>),
-        Blue(noise::Blue<Xoroshiro128Plus>),
-        Violet(noise::Violet<Xoroshiro128Plus>),
-        Brownian(noise::Brownian<Xoroshiro128Plus>),
-        Velvet(noise::Velvet<Xoroshiro128Plus>),
-    }
+/// The algorithm that generates pink noise
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum PinkAlgo {
+    /// The pink noise of rodio
+    #[default]
+    Rodio,
+    /// The Voss-McCartney algorithm, which draws fewer random numbers
+    Voss,
+}
 
-    impl Iterator {
-        type Item = Sample;
+/// The format of the samples written to stdout
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    /// Raw interleaved little-endian samples
+    #[default]
+    Raw,
+    /// A WAV header of unknown length, followed by the samples
+    Wav,
+}
 
-        fn next(&mut self) -> Option<Self::Item>;
+/// How the amplitude changes during a fade or the `--ramp-up`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum FadeCurve {
+    /// The amplitude changes linearly
+    #[default]
+    Linear,
+    /// The loudness changes linearly, i.e. the amplitude exponentially from and to -60 dB
+    Exponential,
+    /// The amplitude starts and ends slowly, and changes quickest in the middle
+    #[value(name = "scurve")]
+    SCurve,
+}
+
+impl FadeCurve {
+    /// The gain after the `progress` from 0 for silence to 1 for the full amplitude
+    #[inline]
+    fn gain(self, progress: f32) -> f32 {
+        match self {
+            Self::Linear => progress,
+            Self::Exponential if progress > 0.0 => 0.001f32.powf(1.0 - progress),
+            Self::Exponential => 0.0,
+            Self::SCurve => progress * progress * (3.0 - 2.0 * progress),
+        }
     }
+}
+
+/// The audio backend, called host by cpal
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Backend {
+    /// The default backend of the platform
+    #[default]
+    Auto,
+    /// The JACK audio server, if the program was built with the "jack" feature
+    Jack,
+    /// ALSA, the sound system of the Linux kernel
+    Alsa,
+    /// The Pulse audio server through its ALSA plugin
+    Pulse,
+}
 
-    impl Source {
-        fn current_span_len(&self) -> Option<usize>;
-        fn channels(&self) -> ChannelCount;
-        fn sample_rate(&self) -> SampleRate;
-        fn total_duration(&self) -> Option<Duration>;
+impl Backend {
+    fn host(self) -> Result<rodio::cpal::Host, Error> {
+        let name = match self {
+            Self::Auto => return Ok(rodio::cpal::default_host()),
+            Self::Jack => "JACK",
+            Self::Alsa | Self::Pulse => "ALSA",
+        };
+        let id = rodio::cpal::available_hosts()
+            .into_iter()
+            .find(|id| id.name() == name)
+            .ok_or(Error::BackendMissing(name))?;
+        rodio::cpal::host_from_id(id).map_err(|_| Error::BackendUnavailable(name))
+    }
 
-        #[inline]
-        fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
-            Ok(())
+    /// The default output device of the backend
+    ///
+    /// For JACK it is a client named after the program, which connects its ports to the playback
+    /// ports of the system.
+    fn default_output_device(self) -> Result<rodio::Device, Error> {
+        #[cfg(all(
+            feature = "jack",
+            any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd"
+            )
+        ))]
+        if self == Self::Jack {
+            let mut host =
+                cpal::platform::JackHost::new().map_err(|_| Error::BackendUnavailable("JACK"))?;
+            return host
+                .output_device_with_name(env!("CARGO_BIN_NAME"))
+                .map(rodio::Device::from)
+                .ok_or(Error::JackServer);
         }
+        if self == Self::Pulse {
+            return self
+                .host()?
+                .output_devices()?
+                .find(|device| device.name().is_ok_and(|name| name == "pulse"))
+                .ok_or(Error::BackendUnavailable("PulseAudio"));
+        }
+        self.host()?
+            .default_output_device()
+            .ok_or(Error::Stream(rodio::StreamError::NoDevice))
     }
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, strum::Display, clap::ValueEnum)]
-#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
-enum NoiseValue {
-    White,
-    Gaussian,
-    Triangular,
-    Pink,
-    Blue,
-    Violet,
+/// The format of the log messages
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum LogFormat {
+    /// Human readable text
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// When to color the log messages
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Color {
+    /// If stderr is a terminal, and the environment variable `NO_COLOR` is not set
+    #[default]
+    Auto,
+    /// Always, even if stderr is redirected
+    Always,
+    /// Never
+    Never,
+}
+
+/// What the first ctrl+C does
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum CtrlcMode {
+    /// Fade out and exit, press ctrl+C twice more to exit immediately
     #[default]
-    Brownian,
-    Velvet,
+    Graceful,
+    /// Exit immediately
+    Immediate,
 }
 
-impl NoiseValue {
-    fn to_noise(self, sample_rate: SampleRate) -> Noise {
-        let func: fn(SampleRate, Xoroshiro128Plus) -> Noise = match self {
-            Self::White => |s, r| Noise::White(noise::WhiteUniform::new_with_rng(s, r)),
-            Self::Gaussian => |s, r| Noise::Gaussian(noise::WhiteGaussian::new_with_rng(s, r)),
-            Self::Triangular => {
-                |s, r| Noise::Triangular(noise::WhiteTriangular::new_with_rng(s, r))
+impl CtrlcMode {
+    const fn escalation(self) -> Escalation {
+        match self {
+            Self::Graceful => Escalation {
+                warn: 2,
+                exit: 3,
+                abort: 4,
+            },
+            Self::Immediate => Escalation {
+                warn: u32::MAX,
+                exit: 1,
+                abort: 2,
+            },
+        }
+    }
+}
+
+impl Args {
+    /// A generator for `noise`, with the requested `--velvet-density`, `--pink-algo`,
+    /// `--brownian-leak`, and `--rng`
+    fn to_noise(
+        &self,
+        noise: NoiseValue,
+        sample_rate: SampleRate,
+        seed: [u8; 16],
+    ) -> Result<Noise, Error> {
+        let rng = self.rng.to_rng(seed);
+        Ok(match (noise, self.velvet_density, self.brownian_leak) {
+            (NoiseValue::Velvet, Some(density), _) => Noise::Velvet(
+                VelvetNoise::with_density(sample_rate, density, rng).map_err(Error::Noise)?,
+            ),
+            (NoiseValue::Pink, _, _) if self.pink_algo == PinkAlgo::Voss => {
+                Noise::Voss(VossNoise::new_with_rng(sample_rate, rng))
             }
-            Self::Pink => |s, r| Noise::Pink(noise::Pink::new_with_rng(s, r)),
-            Self::Blue => |s, r| Noise::Blue(noise::Blue::new_with_rng(s, r)),
-            Self::Violet => |s, r| Noise::Violet(noise::Violet::new_with_rng(s, r)),
-            Self::Brownian => |s, r| Noise::Brownian(noise::Brownian::new_with_rng(s, r)),
-            Self::Velvet => |s, r| Noise::Velvet(noise::Velvet::new_with_rng(s, r)),
+            (NoiseValue::Brownian, _, Some(leak)) => Noise::LeakyBrownian(DcBlocker::new(
+                LeakyBrownian::with_leak(sample_rate, leak, rng).map_err(Error::Noise)?,
+            )),
+            (noise, _, _) => noise.to_noise_with_rng(sample_rate, rng),
+        })
+    }
+
+    /// The loudness compensation for `noise`, unless it was disabled, and the scaling to the
+    /// `--gaussian-stddev` or the `--triangular-width`
+    fn loudness(&self, noise: NoiseValue) -> f32 {
+        let compensation = if self.no_loudness_compensation {
+            1.0
+        } else {
+            noise.loudness_gain()
         };
-        func(sample_rate, Xoroshiro128Plus::from_seed(SEED))
+        match (noise, self.gaussian_stddev) {
+            (NoiseValue::Gaussian, Some(stddev)) => {
+                compensation * stddev.clamp(MIN_GAUSSIAN_STDDEV, MAX_GAUSSIAN_STDDEV)
+                    / DEFAULT_GAUSSIAN_STDDEV
+            }
+            (NoiseValue::Triangular, _) => compensation * self.triangular_width.unwrap_or(1.0),
+            _ => compensation,
+        }
+    }
+
+    /// A description of the played sound, e.g. "brownian noise" or "a 440 Hz tone"
+    fn sound(&self) -> String {
+        let sound = self.generator();
+        match self.blend_tone {
+            Some(frequency) => format!("{sound} with a {frequency} Hz tone"),
+            None => sound,
+        }
+    }
+
+    /// What the selected generator plays, without the `--blend-tone`
+    fn generator(&self) -> String {
+        if self.silent {
+            return "silence".into();
+        }
+        if let Some(frequency) = self.tone {
+            return format!("a {frequency} Hz tone");
+        }
+        if let Some([base, beat]) = self.binaural.as_deref() {
+            return format!("a binaural beat of {beat} Hz on a {base} Hz tone");
+        }
+        if let Some(alpha) = self.slope {
+            return format!("1/f^{alpha} noise");
+        }
+        if self.mix.is_empty() {
+            return format!("{} noise", self.noise);
+        }
+        let names: Vec<String> = self.mix.iter().map(|item| item.noise.to_string()).collect();
+        format!("{} noise", names.join("+"))
     }
 }
 
+/// One noise type in `--mix`, and its weight
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MixItem {
+    noise: NoiseValue,
+    weight: f32,
+}
+
+fn parse_mix_item(item: &str) -> Result<MixItem, String> {
+    let (noise, weight) = item
+        .split_once(':')
+        .ok_or_else(|| format!("expected NOISE:WEIGHT, got {item:?}"))?;
+    let noise = <NoiseValue as clap::ValueEnum>::from_str(noise, true)?;
+    let weight = weight
+        .parse()
+        .ok()
+        .filter(|weight: &f32| weight.is_finite() && *weight >= 0.0)
+        .ok_or_else(|| format!("unsupported weight {weight:?}"))?;
+    Ok(MixItem { noise, weight })
+}
+
+/// The range of `--on-range` and `--off-range`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DurationRange {
+    min: Duration,
+    max: Duration,
+}
+
+impl DurationRange {
+    /// A random duration in the range, in whole milliseconds
+    fn sample(self, rng: &mut impl Rng) -> Duration {
+        #[expect(clippy::cast_possible_truncation)]
+        let (min, max) = (self.min.as_millis() as u64, self.max.as_millis() as u64);
+        Duration::from_millis(rng.random_range(min..=max))
+    }
+}
+
+fn parse_duration_range(range: &str) -> Result<DurationRange, String> {
+    let (min, max) = range
+        .split_once('-')
+        .ok_or_else(|| format!("expected MIN-MAX, got {range:?}"))?;
+    let parse = |duration: &str| {
+        humantime::parse_duration(duration.trim())
+            .map_err(|err| format!("unsupported duration {duration:?}: {err}"))
+    };
+    let (min, max) = (parse(min)?, parse(max)?);
+    if min > max {
+        return Err(format!(
+            "the minimum of {range:?} is longer than the maximum"
+        ));
+    }
+    Ok(DurationRange { min, max })
+}
+
+fn parse_positive(value: &str) -> Result<f32, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|value: &f32| value.is_finite() && *value > 0.0)
+        .ok_or_else(|| format!("expected a positive number, got {value:?}"))
+}
+
+/// One band of `--eq`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct EqBand {
+    frequency: f32,
+    gain_db: f32,
+    q: f32,
+}
+
+fn parse_eq_band(band: &str) -> Result<EqBand, String> {
+    let mut fields = band.split(':');
+    let (Some(frequency), Some(gain_db), Some(q), None) =
+        (fields.next(), fields.next(), fields.next(), fields.next())
+    else {
+        return Err(format!("expected FREQUENCY:GAIN_DB:Q, got {band:?}"));
+    };
+    let frequency = frequency
+        .parse()
+        .ok()
+        .filter(|frequency: &f32| frequency.is_finite() && *frequency > 0.0)
+        .ok_or_else(|| format!("unsupported frequency {frequency:?}"))?;
+    let gain_db = gain_db
+        .parse()
+        .ok()
+        .filter(|gain_db: &f32| gain_db.abs() <= MAX_EQ_GAIN)
+        .ok_or_else(|| format!("unsupported gain {gain_db:?}, expected -24 to 24 dB"))?;
+    let q = q
+        .parse()
+        .ok()
+        .filter(|q: &f32| q.is_finite() && *q > 0.0)
+        .ok_or_else(|| format!("unsupported quality factor {q:?}"))?;
+    Ok(EqBand {
+        frequency,
+        gain_db,
+        q,
+    })
+}
+
+/// The lowest level of the `--blend-tone` in dB
+const MIN_BLEND_TONE_LEVEL: f32 = -80.0;
+
+/// The most bands `--eq` accepts
+const MAX_EQ_BANDS: usize = 8;
+
+/// The largest boost or cut of an `--eq` band in dB
+const MAX_EQ_GAIN: f32 = 24.0;
+
+/// Crossfade into the noise type `noise` while playing
+fn switch_noise(args: &mut Args, shared: &Shared, noise: NoiseValue) {
+    if args.silent
+        || args.tone.is_some()
+        || args.slope.is_some()
+        || args.binaural.is_some()
+        || !args.mix.is_empty()
+    {
+        warn!("The noise type can only be changed if a single noise type is playing.");
+        return;
+    }
+    let previous = std::mem::replace(&mut args.noise, noise);
+    // The new noise replaces a running `--crossfade-to`, also when the stream is reconnected.
+    let crossfade_to = args.crossfade_to.take();
+    let sources = shared
+        .switches
+        .borrow()
+        .iter()
+        .map(|&(_, seed)| mono_source(args, shared.sample_rate.get(), seed))
+        .collect::<Result<Vec<_>, Error>>();
+    match sources {
+        Ok(sources) => {
+            for ((slot, _), source) in shared.switches.borrow().iter().zip(sources) {
+                slot.set(source);
+            }
+            info!("Now playing {}.", args.sound());
+        }
+        Err(err) => {
+            args.noise = previous;
+            args.crossfade_to = crossfade_to;
+            warn!("Could not change the noise type: {err}");
+        }
+    }
+}
+
+/// The standard deviation of rodio's gaussian noise
+const DEFAULT_GAUSSIAN_STDDEV: f32 = 1.0 / 3.0;
+
+/// The supported range of the `--gaussian-stddev`
+const MIN_GAUSSIAN_STDDEV: f32 = 0.01;
+const MAX_GAUSSIAN_STDDEV: f32 = 1.0;
+
+/// The state that the noise generation on the audio thread shares with the main thread
+struct Shared {
+    gain: Arc<Gain>,
+    fading: Arc<AtomicBool>,
+    levels: Option<Arc<Levels>>,
+    /// The levels for `--metrics-addr`, separate from the ones `--meter` displays
+    metrics_levels: Option<Arc<Levels>>,
+    counters: Arc<metrics::Counters>,
+    pan: Arc<AtomicU32>,
+    /// The coefficients of the running filters, in the order of [`filter_coefficients()`]
+    filters: RefCell<Vec<Arc<Tunable>>>,
+    /// The sample rate of the running noise
+    sample_rate: Cell<SampleRate>,
+    /// The mono noise generators that can be replaced, one per channel of `--stereo` and device
+    /// of `--all-devices`, and their seeds
+    switches: RefCell<Vec<(Arc<Slot>, [u8; 16])>>,
+    /// Where the samples go that are recorded with `--tee`
+    recording: Option<tee::Recording>,
+}
+
+impl Shared {
+    fn new(amplitude: f32, meter: bool, metrics: bool) -> Self {
+        Self {
+            gain: Arc::new(Gain::new(amplitude * 0.01)),
+            fading: Arc::new(AtomicBool::new(false)),
+            levels: meter.then(Arc::<Levels>::default),
+            metrics_levels: metrics.then(Arc::<Levels>::default),
+            counters: Arc::default(),
+            pan: Arc::new(AtomicU32::new(0f32.to_bits())),
+            filters: RefCell::default(),
+            sample_rate: Cell::new(DEFAULT_SAMPLE_RATE),
+            switches: RefCell::default(),
+            recording: None,
+        }
+    }
+}
+
+/// A gain factor that can be changed while the noise is playing
+///
+/// It also counts the samples it was applied to, so the [`watchdog`] can notice a stalled stream.
+#[derive(Debug, Default)]
+struct Gain {
+    gain: AtomicU32,
+    /// Silences the noise without changing the gain, see `--pause-after-idle`
+    paused: AtomicBool,
+    heartbeat: AtomicU64,
+}
+
+impl Gain {
+    fn new(gain: f32) -> Self {
+        Self {
+            gain: AtomicU32::new(gain.to_bits()),
+            paused: AtomicBool::new(false),
+            heartbeat: AtomicU64::new(0),
+        }
+    }
+
+    #[inline]
+    fn get(&self) -> f32 {
+        f32::from_bits(self.gain.load(Relaxed))
+    }
+
+    fn set(&self, gain: f32) {
+        self.gain.store(gain.to_bits(), Relaxed);
+    }
+
+    /// The gain to apply to the samples, zero while the noise is paused
+    #[inline]
+    fn output(&self) -> f32 {
+        if self.paused.load(Relaxed) {
+            0.0
+        } else {
+            self.get()
+        }
+    }
+
+    fn pause(&self, paused: bool) {
+        self.paused.store(paused, Relaxed);
+    }
+
+    /// Count another sample, with `--all-devices` the audio thread of every device calls this
+    #[inline]
+    fn beat(&self) {
+        let _: u64 = self.heartbeat.fetch_add(1, Relaxed);
+    }
+
+    fn heartbeat(&self) -> u64 {
+        self.heartbeat.load(Relaxed)
+    }
+}
+
+/// Why the main thread was woken up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Event {
+    /// The user pressed ctrl+C, or the process received SIGTERM
+    Cancelled,
+    /// The requested `--duration` has elapsed
+    Elapsed,
+    /// The requested fade-out has finished
+    FadedOut,
+    /// The audio stream failed, e.g. because the output device was unplugged
+    Disconnected,
+    /// The audio stream stopped pulling samples for longer than the `--watchdog-timeout`
+    Stalled,
+    /// The process received SIGHUP, so the config file should be read again
+    Reload,
+    /// An OSC message asked for another noise type
+    Noise(NoiseValue),
+}
+
 #[derive(pretty_error_debug::Debug, thiserror::Error, displaydoc::Display)]
 enum Error {
     /// Could not set up tracing filter
@@ -156,13 +2104,335 @@ enum Error {
     /// Could not set up tracing registry
     TracingInit(#[from] tracing_subscriber::util::TryInitError),
     /// Cannot trap ctrl+C
+    #[cfg(not(unix))]
     CtrlC(#[from] ctrlc::Error),
+    /// Cannot trap ctrl+C
+    #[cfg(unix)]
+    CtrlC(#[source] std::io::Error),
     /// Could not set up audio stream
     Stream(#[from] rodio::StreamError),
-    /// Unsupported amplitude {0:?}
-    Amplitude(f32),
+    /// Could not read config file {0:?}
+    ConfigRead(PathBuf, #[source] std::io::Error),
+    /// Could not parse config file {0:?}
+    ConfigParse(PathBuf, #[source] toml::de::Error),
+    /// Unknown setting {0:?} in config file
+    ConfigKey(String),
+    /// Unsupported value for setting {0:?} in config file
+    ConfigValue(String),
+    /// Could not find the data directory to remember the settings in
+    StateDir,
+    /// Could not write state file {0:?}
+    StateWrite(PathBuf, #[source] std::io::Error),
+    /// Unknown preset {0:?}, see `--list-presets`
+    Preset(String),
+    /// Invalid settings in the reloaded config file
+    Reload(#[source] clap::Error),
+    /// The audio backend {0} is not supported by this build
+    BackendMissing(&'static str),
+    /// The audio backend {0} is not available
+    BackendUnavailable(&'static str),
+    /// Could not connect to the JACK server, is it running?
+    #[cfg(all(
+        feature = "jack",
+        any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd"
+        )
+    ))]
+    JackServer,
+    /// Could not enumerate output devices
+    Devices(#[from] rodio::DevicesError),
+    /// No usable output device matches {0}, available devices: {1}
+    DeviceNotFound(DeviceNames, DeviceNames),
+    /// Could not query the supported formats of the output device
+    StreamConfigs(#[from] rodio::cpal::SupportedStreamConfigsError),
+    /// The output device does not support {0} samples, supported formats: {1}
+    SampleFormat(rodio::cpal::SampleFormat, SampleFormats),
+    /// Lost the output device
+    Disconnected,
+    /// Could not reconnect to the output device after {0} attempts
+    Reconnect(u32, #[source] Box<Self>),
+    /// Writing a WAV file requires `--duration`
+    OutputDuration,
+    /// `--tee` can only record into WAV files, not to stdout
+    TeeStdout,
+    /// Could not create {0:?} to record the noise
+    Tee(PathBuf, #[source] std::io::Error),
+    /// Could not write WAV file
+    Wav(#[from] hound::Error),
+    /// Could not write to stdout
+    Stdout(#[source] std::io::Error),
+    /// Could not read the power status of the system
+    Battery(#[source] starship_battery::Error),
+    /// Could not read the idle time of the system, it needs systemd-logind or macOS
+    IdleTime,
+    /// Could not listen on {0:?} for metrics
+    Metrics(String, #[source] std::io::Error),
+    /// Could not listen on {0:?} for OSC messages
+    Osc(String, #[source] std::io::Error),
+    /// Could not read envelope file {0:?}
+    EnvelopeRead(PathBuf, #[source] std::io::Error),
+    /// Invalid breakpoint in line {1} of envelope file {0:?}
+    EnvelopeParse(PathBuf, usize, #[source] envelope::LineError),
+    /// The envelope file {0:?} contains no breakpoints
+    EnvelopeEmpty(PathBuf),
+    /// Could not start a background thread
+    Thread(#[source] std::io::Error),
+    /// Could not set up the terminal for keyboard input
+    Terminal(#[source] std::io::Error),
+    /// Could not gather entropy for a random seed
+    Entropy(#[from] rand::rand_core::OsError),
+    /// {0}
+    Noise(noisy_silence::Error),
+    /// Frequency {0:?} Hz is not in the range between 0 Hz and the Nyquist frequency {1} Hz
+    Frequency(f32, f32),
+    /// The pan {0:?} is not in the range between -1 and 1
+    Pan(f32),
+    /// The auto-pan rate {0:?} Hz is not in the range between 0 Hz and 0.2 Hz
+    AutoPanRate(f32),
+    /// Panning is only supported for stereo output, not for {0} channels
+    PanChannels(ChannelCount),
+    /// The Haas delay {0:?} ms is not above 0 ms and at most 40 ms
+    Haas(f32),
+    /// The Haas effect is only supported for stereo output, not for {0} channels
+    HaasChannels(ChannelCount),
+    /// The stereo width is only supported for stereo output, not for {0} channels
+    WidthChannels(ChannelCount),
+    /// The crossfeed amount {0:?} is not in the range between 0 and 1
+    Crossfeed(f32),
+    /// Crossfeed is only supported for stereo output, not for {0} channels
+    CrossfeedChannels(ChannelCount),
+    /// The beat frequency {0:?} Hz must be above 0 Hz
+    Beat(f32),
+    /// Got {0} `--channel-amplitudes`, but the output has {1} channels
+    ChannelAmplitudes(usize, ChannelCount),
+    /// Binaural beats need a stereo output device, not one with {0} channels
+    BinauralChannels(ChannelCount),
+    /// The LFO depth {0:?} is not in the range between 0 and 1
+    LfoDepth(f32),
+    /// The LFO rate {0:?} Hz is not in the range between 0 Hz and 5 Hz
+    LfoRate(f32),
+    /// The compressor threshold {0:?} dBFS must not be above 0 dBFS
+    Threshold(f32),
+    /// The compression ratio {0:?} must be at least 1
+    Ratio(f32),
+    /// The attack and release times must be above 0 ms, not {0:?} ms
+    CompressorTime(f32),
+    /// The on-phase of the duty cycle must be at least one sample long
+    DutyCycle,
+    /// The watchdog timeout must not be zero
+    WatchdogTimeout,
+    /// The rotation interval must not be zero
+    RotateInterval,
+    /// A-weighting needs a sample rate of at least 24.4 kHz, not {0} Hz
+    AWeightSampleRate(SampleRate),
+    /// The weights of `--mix` must not add up to zero
+    MixWeights,
+    /// The tone level {0:?} dB is not in the range between -80 and 0
+    BlendToneLevel(f32),
+    /// Unsupported filter quality factor {0:?}
+    Q(f32),
+}
+
+/// The names of the available output devices
+#[derive(Debug, Clone, Default)]
+struct DeviceNames(Vec<String>);
+
+impl fmt::Display for DeviceNames {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((first, rest)) = self.0.split_first() else {
+            return f.write_str("none");
+        };
+        write!(f, "{first:?}")?;
+        for name in rest {
+            write!(f, ", {name:?}")?;
+        }
+        Ok(())
+    }
 }
 
+/// The sample formats that an output device supports
+#[derive(Debug, Clone, Default)]
+struct SampleFormats(Vec<rodio::cpal::SampleFormat>);
+
+impl fmt::Display for SampleFormats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((first, rest)) = self.0.split_first() else {
+            return f.write_str("none");
+        };
+        write!(f, "{first}")?;
+        for format in rest {
+            write!(f, ", {format}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the first output device whose name contains `name`, ignoring the case
+fn find_device(backend: Backend, name: &str) -> Result<rodio::Device, Error> {
+    let needle = name.to_lowercase();
+    let mut names = Vec::new();
+    for device in backend.host()?.output_devices()? {
+        let Ok(device_name) = device.name() else {
+            continue;
+        };
+        if device_name.to_lowercase().contains(&needle) {
+            return Ok(device);
+        }
+        names.push(device_name);
+    }
+    Err(Error::DeviceNotFound(
+        DeviceNames(vec![name.to_owned()]),
+        DeviceNames(names),
+    ))
+}
+
+/// Try to `open` the output devices `names` in order, and return the first stream that works
+fn open_first(
+    backend: Backend,
+    names: &[String],
+    mut open: impl FnMut(&String) -> Result<(rodio::Device, rodio::OutputStream), Error>,
+) -> Result<(rodio::Device, rodio::OutputStream), Error> {
+    for name in names {
+        match open(name) {
+            Ok(stream) => return Ok(stream),
+            Err(err) => {
+                warn!("Could not open the output device {name:?}: {err}")
+            }
+        }
+    }
+    let available = backend
+        .host()?
+        .output_devices()?
+        .filter_map(|device| device.name().ok())
+        .collect();
+    Err(Error::DeviceNotFound(
+        DeviceNames(names.to_vec()),
+        DeviceNames(available),
+    ))
+}
+
+fn list_devices(backend: Backend) -> Result<(), Error> {
+    let mut out = stdout().lock();
+    for device in device_inventory(backend)? {
+        let _: std::io::Result<()> = writeln!(out, "{device}");
+    }
+    Ok(())
+}
+
+fn list_noise_types() {
+    let mut out = stdout().lock();
+    for &noise in NoiseValue::all() {
+        let name = noise.to_string();
+        let _: std::io::Result<()> = writeln!(out, "{name:<10}  {}", noise.description());
+    }
+}
+
+/// Print the level of every octave band of `noise`, after the configured filters, and the slope
+/// of its spectrum
+///
+/// The noise is generated from the default seed, so the output is always the same.
+fn print_spectrum(args: &Args, noise: NoiseValue) -> Result<(), Error> {
+    let sample_rate = args.sample_rate.unwrap_or(DEFAULT_SAMPLE_RATE);
+    let (source, sound): (BoxedSource, _) = match args.slope {
+        Some(alpha) => {
+            let noise = SlopeNoise::with_rng(sample_rate, alpha, args.rng.to_rng(SEED))
+                .map_err(Error::Noise)?;
+            (Box::new(noise), format!("1/f^{alpha} noise"))
+        }
+        None => (
+            Box::new(args.to_noise(noise, sample_rate, SEED)?),
+            format!("{noise} noise"),
+        ),
+    };
+    let mut source = band_limit(args, sample_rate, source)?;
+    for coefficients in filter_coefficients(args, sample_rate)? {
+        source = Box::new(Biquad::new(source, Arc::new(Tunable::new(coefficients))));
+    }
+    let spectrum = spectrum::Spectrum::measure(source, sample_rate);
+
+    let mut out = stdout().lock();
+    let _: std::io::Result<()> = writeln!(out, "Spectrum of {sound} at {sample_rate} Hz:");
+    for (center, level) in &spectrum.bands {
+        let _: std::io::Result<()> = writeln!(out, "{center:>8} Hz  {level:>6.1} dBFS");
+    }
+    let _: std::io::Result<()> = writeln!(
+        out,
+        "Slope: {:.2} dB per octave, like 1/f^{:.2}",
+        spectrum.slope,
+        spectrum.alpha(),
+    );
+    Ok(())
+}
+
+/// A line for every output device that cpal can see, the default device is marked with a star
+fn device_inventory(backend: Backend) -> Result<Vec<String>, Error> {
+    let host = backend.host()?;
+    let default = host.default_output_device().and_then(|d| d.name().ok());
+    let mut devices = Vec::new();
+    for device in host.output_devices()? {
+        let Ok(name) = device.name() else {
+            continue;
+        };
+        let marker = if default.as_ref() == Some(&name) {
+            '*'
+        } else {
+            ' '
+        };
+        devices.push(match device.default_output_config() {
+            Ok(config) => format!(
+                "{marker} {name} ({} Hz, {} channels)",
+                config.sample_rate().0,
+                config.channels(),
+            ),
+            Err(_) => format!("{marker} {name}"),
+        });
+    }
+    Ok(devices)
+}
+
+/// Explain which output devices could have been opened instead
+fn log_device_inventory(backend: Backend) {
+    if backend.default_output_device().is_err() {
+        warn!("There is no default output device.");
+    }
+    match device_inventory(backend) {
+        Ok(devices) if devices.is_empty() => warn!("No output devices were found."),
+        Ok(devices) => {
+            warn!("The available output devices are, the default one is marked with a star:");
+            for device in devices {
+                warn!("{device}");
+            }
+        }
+        Err(err) => warn!("Could not list the output devices: {err}"),
+    }
+}
+
+/// A type-erased source, so that optional stages can be chained at runtime
+type BoxedSource = Box<dyn Source + Send>;
+
+/// The number of interleaved samples `source` produces in `duration`, rounded to whole frames
+#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn duration_to_samples(source: &impl Source, duration: Duration) -> u64 {
+    let frames = (duration.as_secs_f64() * f64::from(source.sample_rate())).round() as u64;
+    frames * u64::from(source.channels())
+}
+
+/// The output of `--version`, with the details of the build that are needed in bug reports
+const LONG_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    "\ncommit:   ",
+    env!("NOISY_GIT_COMMIT"),
+    "\nbuilt:    ",
+    env!("NOISY_BUILD_DATE"),
+    "\ncompiler: ",
+    env!("NOISY_RUSTC_VERSION"),
+    "\nfeatures: ",
+    env!("NOISY_FEATURES"),
+);
+
 fn long_about() -> &'static str {
     include_str!("../README.md")
         .split_once("\r\n\r\n")
@@ -170,4 +2440,124 @@ fn long_about() -> &'static str {
         .1
 }
 
+/// Derive a seed from the phrase `seed` with the 128 bit SipHash-1-3, keyed with [`SEED`]
+///
+/// Never change this function, or the same phrase would yield different noise.
+fn hash_seed(seed: &str) -> [u8; 16] {
+    SipHasher13::new_with_key(&SEED)
+        .hash(seed.as_bytes())
+        .as_bytes()
+}
+
+/// Handle ctrl+C: end the program gracefully at first, then more and more forcefully
+fn cancel(tx: &mpsc::SyncSender<Event>, mode: CtrlcMode) {
+    static CANCELLED: AtomicU32 = AtomicU32::new(0);
+
+    let escalation = mode.escalation();
+    let count = CANCELLED.fetch_add(1, SeqCst) + 1;
+    if count >= escalation.abort {
+        interactive::restore_terminal();
+        abort();
+    } else if count >= escalation.exit {
+        interactive::restore_terminal();
+        exit(0);
+    } else if count == escalation.warn {
+        warn!("Trapped ctrl+C twice. Press ctrc+C again to kill the process.");
+    }
+    let _: Result<(), mpsc::SendError<Event>> = tx.send(Event::Cancelled);
+}
+
+/// After how many presses of ctrl+C the program reacts more forcefully
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Escalation {
+    /// Warn that the next ctrl+C kills the process
+    warn: u32,
+    /// Exit immediately, without fading out
+    exit: u32,
+    /// Abort the process, in case exiting got stuck
+    abort: u32,
+}
+
+/// Ensure that `frequency` is above zero and below the Nyquist frequency
+#[expect(clippy::cast_precision_loss)]
+fn check_frequency(sample_rate: SampleRate, frequency: f32) -> Result<f32, Error> {
+    let nyquist = sample_rate as f32 / 2.0;
+    if frequency > 0.0 && frequency < nyquist {
+        Ok(frequency)
+    } else {
+        Err(Error::Frequency(frequency, nyquist))
+    }
+}
+
+/// Ensure that the filter quality factor `q` is finite and above zero
+fn check_q(q: f32) -> Result<f32, Error> {
+    if q.is_finite() && q > 0.0 {
+        Ok(q)
+    } else {
+        Err(Error::Q(q))
+    }
+}
+
+/// Derive an independent seed for each of the output channels, the first channel keeps `seed`
+fn channel_seed(seed: [u8; 16], channel: u16) -> [u8; 16] {
+    match channel {
+        0 => seed,
+        _ => SipHasher13::new_with_key(&seed)
+            .hash(&channel.to_le_bytes())
+            .as_bytes(),
+    }
+}
+
+const DEFAULT_SAMPLE_RATE: SampleRate = 48_000;
+
+/// The shortest fade-in, so that not even `--amplitude 100` starts at its full level
+const MIN_FADE_IN: Duration = Duration::from_millis(250);
+
+/// How much of the noise `--dry-run` generates
+const DRY_RUN_DURATION: Duration = Duration::from_secs(10);
+
+/// The seed of the noise if no `--seed` was given
 const SEED: [u8; 16] = *b"Enjoy t. silence";
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    /// The source that `args` would play, mono at 48 kHz
+    fn build(args: &[&str]) -> FadeOut<BoxedSource> {
+        let args =
+            Args::try_parse_from(std::iter::once("noisy-silence").chain(args.iter().copied()))
+                .unwrap();
+        let (tx, _) = mpsc::sync_channel(1);
+        let shared = Shared::new(10.0, false, false);
+        build_source(&args, 48_000, 1, &shared, SEED, tx).unwrap()
+    }
+
+    #[test]
+    fn total_duration_follows_duration() {
+        assert_eq!(
+            build(&["--duration", "5s"]).total_duration(),
+            Some(Duration::from_secs(5)),
+        );
+        assert_eq!(build(&[]).total_duration(), None);
+    }
+
+    #[test]
+    fn seed_phrase_is_stable() {
+        // Changing these values would change the noise of every `--seed-phrase`.
+        assert_eq!(
+            hash_seed("Enjoy the silence"),
+            [
+                98, 153, 190, 155, 86, 43, 90, 254, 135, 130, 24, 134, 248, 98, 27, 60
+            ],
+        );
+        assert_eq!(
+            hash_seed(""),
+            [
+                76, 112, 113, 159, 194, 220, 40, 111, 185, 91, 113, 120, 178, 203, 8, 138
+            ],
+        );
+    }
+}
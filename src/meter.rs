@@ -0,0 +1,22 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use noisy_silence::{Level, Levels};
+
+use crate::Error;
+
+/// Display the levels a few times per second in a background thread
+pub(crate) fn spawn(levels: Arc<Levels>) -> Result<(), Error> {
+    let _: std::thread::JoinHandle<()> = levels
+        .watch(INTERVAL, |level| {
+            if let Some(Level { peak, rms }) = level {
+                eprint!("\rPeak: {peak:6.1} dBFS, RMS: {rms:6.1} dBFS  ");
+            }
+            true
+        })
+        .map_err(Error::Thread)?;
+    Ok(())
+}
+
+/// How often the levels are displayed
+const INTERVAL: Duration = Duration::from_millis(250);
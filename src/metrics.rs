@@ -0,0 +1,187 @@
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64};
+use std::thread;
+use std::time::Duration;
+
+use noisy_silence::{Level, Levels};
+use tracing::{debug, info};
+
+use crate::{Error, Gain};
+
+/// The counters that are only needed for the metrics
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    /// How often the stream was reconnected
+    pub(crate) reconnects: AtomicU32,
+    /// How many samples the limiter clamped
+    pub(crate) limited: AtomicU64,
+    /// The bits of the highest absolute sample value, see [`f32::to_bits()`]
+    pub(crate) peak: AtomicU32,
+    /// How many samples exceeded 99% of the full scale
+    pub(crate) loud: AtomicU64,
+}
+
+/// A tiny HTTP server that exposes the state of the noise in the Prometheus text format
+///
+/// Dropping it stops the server.
+#[derive(Debug)]
+pub(crate) struct Server {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+/// Start serving the metrics on `addr`
+pub(crate) fn spawn(
+    addr: &str,
+    gain: Arc<Gain>,
+    levels: Arc<Levels>,
+    counters: Arc<Counters>,
+) -> Result<Server, Error> {
+    let listener = TcpListener::bind(addr)
+        .and_then(|listener| Ok((listener.local_addr()?, listener)))
+        .map_err(|err| Error::Metrics(addr.to_owned(), err));
+    let (addr, listener) = listener?;
+    info!("Serving metrics on http://{addr}/metrics.");
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread = thread::Builder::new()
+        .name("metrics".into())
+        .spawn({
+            let stop = Arc::clone(&stop);
+            move || {
+                for stream in listener.incoming() {
+                    if stop.load(Relaxed) {
+                        break;
+                    }
+                    let result = stream.and_then(|stream| {
+                        let metrics = render(&gain, &levels, &counters);
+                        respond(stream, &metrics)
+                    });
+                    if let Err(err) = result {
+                        debug!("Could not serve the metrics: {err}");
+                    }
+                }
+            }
+        })
+        .map_err(Error::Thread)?;
+    Ok(Server {
+        addr,
+        stop,
+        thread: Some(thread),
+    })
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stop.store(true, Relaxed);
+        // Wake up the thread, which is waiting for the next connection.
+        let mut addr = self.addr;
+        if addr.ip().is_unspecified() {
+            addr.set_ip(match addr {
+                SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+                SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+            });
+        }
+        if TcpStream::connect_timeout(&addr, Duration::from_secs(1)).is_ok()
+            && let Some(thread) = self.thread.take()
+        {
+            let _: thread::Result<()> = thread.join();
+        }
+    }
+}
+
+/// Answer a single request, every path but `/` and `/metrics` is unknown
+fn respond(mut stream: TcpStream, metrics: &str) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < MAX_REQUEST {
+        let len = stream.read(&mut buffer)?;
+        if len == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..len]);
+    }
+    let mut words = request.split(|&b| b == b' ');
+    let (status, body) = match (words.next(), words.next()) {
+        (Some(b"GET"), Some(b"/" | b"/metrics")) => ("200 OK", metrics),
+        (Some(b"GET"), Some(_)) => ("404 Not Found", "Not found\n"),
+        _ => ("405 Method Not Allowed", "Method not allowed\n"),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: text/plain; version=0.0.4; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\
+         \r\n\
+         {body}",
+        body.len(),
+    )?;
+    stream.flush()
+}
+
+/// The current metrics in the Prometheus text format
+fn render(gain: &Gain, levels: &Levels, counters: &Counters) -> String {
+    let mut out = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, value: &dyn std::fmt::Display| {
+        let _: std::fmt::Result = writeln!(
+            out,
+            "# HELP noisy_silence_{name} {help}\n\
+             # TYPE noisy_silence_{name} {kind}\n\
+             noisy_silence_{name} {value}",
+        );
+    };
+    metric(
+        "amplitude_percent",
+        "gauge",
+        "The current output amplitude in percent.",
+        &(gain.get() * 100.0),
+    );
+    if let Some(Level { peak, rms }) = levels.take() {
+        metric(
+            "peak_dbfs",
+            "gauge",
+            "The peak level in dBFS since the last scrape.",
+            &peak,
+        );
+        metric(
+            "rms_dbfs",
+            "gauge",
+            "The RMS level in dBFS since the last scrape.",
+            &rms,
+        );
+    }
+    metric(
+        "samples_total",
+        "counter",
+        "The number of samples that were played.",
+        &gain.heartbeat(),
+    );
+    metric(
+        "reconnects_total",
+        "counter",
+        "How often the output device was reconnected.",
+        &counters.reconnects.load(Relaxed),
+    );
+    metric(
+        "limited_samples_total",
+        "counter",
+        "The number of samples that the limiter clamped to the full scale.",
+        &counters.limited.load(Relaxed),
+    );
+    out
+}
+
+/// How long a client may take to send its request or to receive the response
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The longest request that is read, the rest is ignored
+const MAX_REQUEST: usize = 8192;
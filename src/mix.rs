@@ -0,0 +1,239 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::{AcqRel, Relaxed, Release};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+use crate::{BoxedSource, duration_to_samples};
+
+/// Sums up mono sources, each multiplied by its weight
+pub(crate) struct MixedNoise<S> {
+    inputs: Vec<(S, f32)>,
+}
+
+impl<S: Source> MixedNoise<S> {
+    pub(crate) fn new(inputs: Vec<(S, f32)>) -> Self {
+        debug_assert!(!inputs.is_empty() && inputs.iter().all(|(input, _)| input.channels() == 1));
+        Self { inputs }
+    }
+}
+
+impl<S: Source> Iterator for MixedNoise<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inputs
+            .iter_mut()
+            .try_fold(0.0, |sum, (input, weight)| {
+                Some(sum + input.next()? * *weight)
+            })
+    }
+}
+
+impl<S: Source> Source for MixedNoise<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.inputs[0].0.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inputs[0].0.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inputs
+            .iter_mut()
+            .try_for_each(|(input, _)| input.try_seek(pos))
+    }
+}
+
+/// The next source of a [`Switch`], which can be set from another thread
+#[derive(Default)]
+pub(crate) struct Slot {
+    source: Mutex<Option<BoxedSource>>,
+    pending: AtomicBool,
+}
+
+impl Slot {
+    pub(crate) fn set(&self, source: BoxedSource) {
+        *self.source.lock().unwrap_or_else(PoisonError::into_inner) = Some(source);
+        self.pending.store(true, Release);
+    }
+
+    /// The new source if one was set, the audio thread never waits for the lock
+    #[inline]
+    fn poll(&self) -> Option<BoxedSource> {
+        // Clear the flag before reading, so a concurrent `set()` raises it again.
+        if !self.pending.swap(false, AcqRel) {
+            return None;
+        }
+        let Ok(mut source) = self.source.try_lock() else {
+            // Try again with the next sample.
+            self.pending.store(true, Relaxed);
+            return None;
+        };
+        source.take()
+    }
+}
+
+/// A mono source that can be replaced while playing, the new source is crossfaded in
+pub(crate) struct Switch {
+    current: BoxedSource,
+    previous: Option<BoxedSource>,
+    slot: Arc<Slot>,
+    elapsed: u64,
+    length: u64,
+}
+
+impl Switch {
+    pub(crate) fn new(current: BoxedSource, slot: Arc<Slot>) -> Self {
+        let length = duration_to_samples(&current, SWITCH_DURATION);
+        Self {
+            current,
+            previous: None,
+            slot,
+            elapsed: 0,
+            length,
+        }
+    }
+}
+
+impl Iterator for Switch {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(source) = self.slot.poll() {
+            self.previous = Some(std::mem::replace(&mut self.current, source));
+            self.elapsed = 0;
+        }
+        let to = self.current.next()?;
+        let Some(previous) = &mut self.previous else {
+            return Some(to);
+        };
+        let from = previous.next().unwrap_or_default();
+        #[expect(clippy::cast_precision_loss)]
+        let weight = (self.elapsed as f32 / self.length as f32).clamp(0.0, 1.0);
+        self.elapsed += 1;
+        if self.elapsed >= self.length {
+            self.previous = None;
+        }
+        Some(from * (1.0 - weight) + to * weight)
+    }
+}
+
+impl Source for Switch {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.current.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Ok(())
+    }
+}
+
+/// How long a [`Switch`] crossfades into its new source
+const SWITCH_DURATION: Duration = Duration::from_millis(500);
+
+/// Linearly blends one mono source into another, then plays only the latter
+pub(crate) struct CrossfadeNoise<S, T> {
+    from: Option<S>,
+    to: T,
+    elapsed: u64,
+    length: u64,
+}
+
+impl<S: Source, T: Source> CrossfadeNoise<S, T> {
+    pub(crate) fn new(from: S, to: T, duration: Duration) -> Self {
+        debug_assert!(from.channels() == 1 && to.channels() == 1);
+        let length = duration_to_samples(&to, duration);
+        Self {
+            from: (length > 0).then_some(from),
+            to,
+            elapsed: 0,
+            length,
+        }
+    }
+}
+
+impl<S: Source, T: Source> Iterator for CrossfadeNoise<S, T> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let to = self.to.next()?;
+        let Some(from) = &mut self.from else {
+            return Some(to);
+        };
+        let from = from.next()?;
+        #[expect(clippy::cast_precision_loss)]
+        let weight = (self.elapsed as f32 / self.length as f32).clamp(0.0, 1.0);
+        self.elapsed += 1;
+        if self.elapsed >= self.length {
+            self.from = None;
+        }
+        Some(from * (1.0 - weight) + to * weight)
+    }
+}
+
+impl<S: Source, T: Source> Source for CrossfadeNoise<S, T> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.to.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.to.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        if let Some(from) = &mut self.from {
+            from.try_seek(pos)?;
+        }
+        self.to.try_seek(pos)
+    }
+}
@@ -0,0 +1,76 @@
+use std::net::UdpSocket;
+use std::sync::{Arc, mpsc};
+use std::thread;
+
+use noisy_silence::NoiseValue;
+use rosc::{OscPacket, OscType};
+use tracing::{debug, info, warn};
+
+use crate::{Error, Event, Gain};
+
+/// Spawn a thread that receives OSC messages on the UDP socket `addr`
+///
+/// `/amplitude <float>` changes the amplitude in percent, and `/noise <string>` crossfades into
+/// another noise type. Invalid messages are logged and ignored.
+pub(crate) fn spawn(addr: &str, gain: Arc<Gain>, tx: mpsc::SyncSender<Event>) -> Result<(), Error> {
+    let socket = UdpSocket::bind(addr).map_err(|err| Error::Osc(addr.to_owned(), err))?;
+    if let Ok(addr) = socket.local_addr() {
+        info!("Listening for OSC messages on {addr}.");
+    }
+    let _: thread::JoinHandle<()> = thread::Builder::new()
+        .name("osc".into())
+        .spawn(move || {
+            let mut buffer = [0; rosc::decoder::MTU];
+            loop {
+                let len = match socket.recv_from(&mut buffer) {
+                    Ok((len, _)) => len,
+                    Err(err) => {
+                        debug!("Could not receive an OSC packet: {err}");
+                        continue;
+                    }
+                };
+                match rosc::decoder::decode_udp(&buffer[..len]) {
+                    Ok((_, packet)) => handle(packet, &gain, &tx),
+                    Err(err) => warn!("Ignoring an invalid OSC packet: {err}"),
+                }
+            }
+        })
+        .map_err(Error::Thread)?;
+    Ok(())
+}
+
+fn handle(packet: OscPacket, gain: &Gain, tx: &mpsc::SyncSender<Event>) {
+    let message = match packet {
+        OscPacket::Message(message) => message,
+        OscPacket::Bundle(bundle) => {
+            for packet in bundle.content {
+                handle(packet, gain, tx);
+            }
+            return;
+        }
+    };
+    #[expect(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    match (message.addr.as_str(), message.args.as_slice()) {
+        ("/amplitude", [OscType::Float(amplitude)]) => set_amplitude(gain, *amplitude),
+        ("/amplitude", [OscType::Double(amplitude)]) => set_amplitude(gain, *amplitude as f32),
+        ("/amplitude", [OscType::Int(amplitude)]) => set_amplitude(gain, *amplitude as f32),
+        ("/noise", [OscType::String(name)]) => {
+            if let Ok(noise) = name.parse::<NoiseValue>() {
+                let _: Result<(), mpsc::SendError<Event>> = tx.send(Event::Noise(noise));
+            } else {
+                warn!("Ignoring the unknown noise type {name:?} in an OSC message.");
+            }
+        }
+        _ => warn!("Ignoring the unsupported OSC message {message}."),
+    }
+}
+
+fn set_amplitude(gain: &Gain, amplitude: f32) {
+    match noisy_silence::check_amplitude(amplitude) {
+        Ok(amplitude) => {
+            gain.set(amplitude * 0.01);
+            info!("Changed the amplitude to {amplitude:.2}%.");
+        }
+        Err(err) => warn!("Ignoring an OSC message: {err}"),
+    }
+}
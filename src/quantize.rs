@@ -0,0 +1,183 @@
+use std::io::{self, Write};
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoroshiro128Plus;
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+use siphasher::sip128::SipHasher13;
+
+/// The format of the samples in `--output` files and streams
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum SampleFormat {
+    /// 16-bit signed integer
+    #[value(name = "16")]
+    S16,
+    /// 24-bit signed integer, in three bytes
+    #[value(name = "24")]
+    S24,
+    /// 32-bit signed integer
+    #[value(name = "32")]
+    S32,
+    /// 32-bit IEEE float
+    #[value(name = "32f")]
+    F32,
+}
+
+impl SampleFormat {
+    /// The size of a sample in bits
+    pub(crate) fn bits(self) -> u16 {
+        match self {
+            Self::S16 => 16,
+            Self::S24 => 24,
+            Self::F32 | Self::S32 => 32,
+        }
+    }
+}
+
+/// The format of the samples that the output device receives with `--sample-format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum StreamFormat {
+    /// 32-bit IEEE float
+    F32,
+    /// 16-bit signed integer
+    I16,
+    /// 16-bit unsigned integer
+    U16,
+}
+
+impl StreamFormat {
+    pub(crate) fn to_cpal(self) -> rodio::cpal::SampleFormat {
+        match self {
+            Self::F32 => rodio::cpal::SampleFormat::F32,
+            Self::I16 => rodio::cpal::SampleFormat::I16,
+            Self::U16 => rodio::cpal::SampleFormat::U16,
+        }
+    }
+}
+
+/// The noise added to the samples before they are rounded to integers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum Dither {
+    /// No dither, just rounding
+    None,
+    /// Triangular probability density, between -1 and 1 LSB
+    Tpdf,
+    /// Rectangular probability density, between -0.5 and 0.5 LSB
+    Rpdf,
+}
+
+/// Converts float samples into the selected sample format
+#[derive(Debug, Clone)]
+pub(crate) struct Quantizer {
+    format: SampleFormat,
+    dither: Dither,
+    rng: Xoroshiro128Plus,
+    /// The value of a full scale sample
+    scale: f64,
+}
+
+impl Quantizer {
+    /// Integer formats default to TPDF dither, floats are never dithered
+    ///
+    /// The dither is derived from the noise's `seed`, so it is reproducible, too.
+    pub(crate) fn new(format: SampleFormat, dither: Option<Dither>, seed: [u8; 16]) -> Self {
+        let dither = match format {
+            SampleFormat::F32 => Dither::None,
+            _ => dither.unwrap_or(Dither::Tpdf),
+        };
+        Self {
+            format,
+            dither,
+            rng: Xoroshiro128Plus::from_seed(
+                SipHasher13::new_with_key(&seed).hash(b"dither").as_bytes(),
+            ),
+            scale: f64::from(1u32 << (format.bits() - 1)),
+        }
+    }
+
+    pub(crate) fn format(&self) -> SampleFormat {
+        self.format
+    }
+
+    /// Scale, dither and round `sample` to an integer sample
+    pub(crate) fn quantize(&mut self, sample: Sample) -> i32 {
+        let dither = match self.dither {
+            Dither::None => 0.0,
+            Dither::Tpdf => self.rng.random::<f64>() - self.rng.random::<f64>(),
+            Dither::Rpdf => self.rng.random::<f64>() - 0.5,
+        };
+        let sample = (f64::from(sample) * self.scale + dither).round();
+        #[expect(clippy::cast_possible_truncation)]
+        let sample = sample.clamp(-self.scale, self.scale - 1.0) as i32;
+        sample
+    }
+
+    /// Scale, dither and round `sample` to an integer sample, and scale it back to a float
+    pub(crate) fn round(&mut self, sample: Sample) -> Sample {
+        #[expect(clippy::cast_possible_truncation)]
+        let sample = (f64::from(self.quantize(sample)) / self.scale) as Sample;
+        sample
+    }
+
+    /// Write `sample` as little-endian bytes
+    pub(crate) fn write_le(&mut self, out: &mut impl Write, sample: Sample) -> io::Result<()> {
+        if self.format == SampleFormat::F32 {
+            return out.write_all(&sample.to_le_bytes());
+        }
+        let bytes = self.quantize(sample).to_le_bytes();
+        out.write_all(&bytes[..usize::from(self.format.bits() / 8)])
+    }
+}
+
+/// Rounds the samples of a source to the values of an integer sample format
+///
+/// The output stream converts these floats exactly, so the samples are dithered by the
+/// [`Quantizer`] instead of just being truncated.
+pub(crate) struct Requantize<S> {
+    input: S,
+    quantizer: Quantizer,
+}
+
+impl<S> Requantize<S> {
+    pub(crate) fn new(input: S, quantizer: Quantizer) -> Self {
+        Self { input, quantizer }
+    }
+}
+
+impl<S: Source> Iterator for Requantize<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        Some(self.quantizer.round(sample))
+    }
+}
+
+impl<S: Source> Source for Requantize<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::atomic::Ordering::Relaxed;
+
+use tracing::{info, warn};
+
+use crate::{Args, Error, Shared, check_pan, config, filter_coefficients, switch_noise};
+
+/// Read the config file again, and apply the settings that can be changed while playing
+///
+/// `loaded` are the settings that were read last, they are compared with the new ones. Invalid
+/// settings are logged, and the noise keeps playing with the old ones. A setting that cannot be
+/// changed while playing is logged once, when the config file changes it.
+pub(crate) fn reload(args: &mut Args, loaded: &mut Args, shared: &Shared) {
+    info!("Reloading the config file.");
+    match config::reload_args().and_then(|new| apply(args, loaded, &new, shared).map(|()| new)) {
+        Ok(new) => *loaded = new,
+        Err(err) => warn!("Could not reload the config file: {err}"),
+    }
+}
+
+/// Update the shared state with the changed settings, and copy them into `args`, so a reconnected
+/// stream uses them, too
+fn apply(args: &mut Args, loaded: &Args, new: &Args, shared: &Shared) -> Result<(), Error> {
+    // Check all settings before changing anything.
+    let amplitude = crate::amplitude(new)?;
+    let pan = new.pan.map(check_pan).transpose()?;
+    let same_filters = [
+        (loaded.a_weight, new.a_weight),
+        (loaded.lowpass.is_some(), new.lowpass.is_some()),
+        (loaded.highpass.is_some(), new.highpass.is_some()),
+        (loaded.bandpass.is_some(), new.bandpass.is_some()),
+        (loaded.notch.is_some(), new.notch.is_some()),
+    ]
+    .iter()
+    .all(|(old, new)| old == new)
+        && loaded.eq.len() == new.eq.len();
+    let filters = if same_filters {
+        Some(filter_coefficients(new, shared.sample_rate.get())?)
+    } else {
+        None
+    };
+
+    // Keep an amplitude that was changed interactively, unless the config file changed it, too.
+    // The `Volume` ramps the gain, so the change does not click.
+    #[expect(clippy::float_cmp)]
+    if amplitude != crate::amplitude(loaded)? {
+        shared.gain.set(amplitude * 0.01);
+        info!("Changed the amplitude to {amplitude:.2}%.");
+    }
+    (args.amplitude, args.amplitude_db) = (new.amplitude, new.amplitude_db);
+
+    match (loaded.pan, pan) {
+        (Some(_), Some(pan)) => {
+            shared.pan.store(pan.to_bits(), Relaxed);
+            args.pan = Some(pan);
+        }
+        (None, None) => {}
+        _ => requires_restart("pan"),
+    }
+
+    if let Some(filters) = filters {
+        for (tunable, coefficients) in shared.filters.borrow().iter().zip(filters) {
+            tunable.set(coefficients);
+        }
+        args.lowpass = new.lowpass;
+        args.highpass = new.highpass;
+        args.bandpass = new.bandpass;
+        args.q = new.q;
+        args.notch = new.notch;
+        args.notch_q = new.notch_q;
+        args.eq.clone_from(&new.eq);
+    } else {
+        requires_restart("the set of filters");
+    }
+
+    // Like the amplitude, keep a noise type that was changed over OSC or by `--rotate-every`.
+    if new.noise != loaded.noise {
+        switch_noise(args, shared, new.noise);
+    }
+
+    for name in fixed_changes(loaded, new) {
+        requires_restart(&name.replace('_', "-"));
+    }
+    Ok(())
+}
+
+/// The names of the fields that differ between `old` and `new`, but are only read when the noise
+/// starts
+fn fixed_changes(old: &Args, new: &Args) -> Vec<&'static str> {
+    macro_rules! fixed_changes {
+        (live: $($live:ident),* ; fixed: $($fixed:ident),* $(,)?) => {{
+            // Every field is listed, so a new setting cannot be forgotten here.
+            let Args { $($live: _,)* $($fixed,)* } = new;
+            let mut changed = Vec::new();
+            $(
+                if *$fixed != old.$fixed {
+                    changed.push(stringify!($fixed));
+                }
+            )*
+            changed
+        }};
+    }
+
+    fixed_changes!(
+        live: noise, amplitude, amplitude_db, pan, lowpass, highpass, a_weight, bandpass, q, notch,
+            notch_q, eq;
+        fixed: channel_amplitudes, battery_amplitude, envelope, floor, mix, slope, tone,
+            blend_tone, blend_tone_level, silent, binaural, gaussian_stddev, triangular_width,
+            brownian_leak, velvet_density, pink_algo, rng, crossfade_to, crossfade_duration,
+            rotate_every, rotate, no_loudness_compensation, no_limiter, stereo, channels,
+            auto_pan_rate, width, crossfeed, haas, bandlimit, compress, threshold_db, ratio,
+            attack_ms, release_ms, lfo_rate, lfo_depth, on, off, intermittent, on_range,
+            off_range, duration, delay, fade_in, ramp_up, ramp_curve, fade_out, fade_curve,
+            ctrlc_mode, seed, random_seed, output, tee, dry_run, output_format, bit_depth,
+            dither, sample_format, buffer_frames, sample_rate, device, all_devices,
+            decorrelate_devices, list_devices, reconnect_retries, pause_after_idle,
+            watchdog_timeout, osc_listen, metrics_addr, config, preset, remember,
+            list_noise_types, list_presets, info, quiet, backend, log_format, color, meter,
+            interactive, license, completions,
+    )
+}
+
+fn requires_restart(setting: &str) {
+    warn!("Changing {setting} requires restart.");
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> Args {
+        Args::try_parse_from(std::iter::once("noisy-silence").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn fixed_changes_ignore_live_settings() {
+        let old = parse(&["brownian", "5"]);
+        let new = parse(&["pink", "10", "--pan", "0.5", "--lowpass", "500"]);
+        assert!(fixed_changes(&old, &new).is_empty());
+        let new = parse(&["brownian", "5", "--haas", "10", "--fade-in", "2s"]);
+        assert_eq!(fixed_changes(&old, &new), ["haas", "fade_in"]);
+    }
+}
@@ -0,0 +1,80 @@
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rand_xoshiro::{Xoroshiro128Plus, Xoshiro256PlusPlus};
+
+nodyn::nodyn! {
+    /// A random number generator of any of the supported algorithms
+    ///
+    /// Use [`RngValue::to_rng()`] to create one.
+    #[derive(Debug, Clone)]
+    pub enum NoiseRng {
+        /// The xoroshiro128+ generator
+        Xoroshiro128Plus(Xoroshiro128Plus),
+        /// The xoshiro256++ generator
+        Xoshiro256PlusPlus(Xoshiro256PlusPlus),
+        /// The ChaCha stream cipher with 8 rounds
+        ChaCha8(ChaCha8Rng),
+    }
+
+    impl RngCore {
+        fn next_u32(&mut self) -> u32;
+        fn next_u64(&mut self) -> u64;
+        fn fill_bytes(&mut self, dst: &mut [u8]);
+    }
+}
+
+/// Seeding a [`NoiseRng`] directly always selects the default algorithm
+impl SeedableRng for NoiseRng {
+    type Seed = [u8; 16];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        RngValue::default().to_rng(seed)
+    }
+}
+
+/// The supported random number generators
+///
+/// The generator can be displayed as and parsed from its lowercase name, e.g. `"chacha8"`,
+/// ignoring the case when parsing.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::Display,
+    strum::EnumString,
+    strum::VariantArray,
+)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "cli", value(rename_all = "lower"))]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum RngValue {
+    /// xoroshiro128+, fast and small
+    #[default]
+    Xoroshiro128Plus,
+    /// xoshiro256++, fast with a longer period
+    Xoshiro256PlusPlus,
+    /// ChaCha with 8 rounds, cryptographically secure but slower
+    ChaCha8,
+}
+
+impl RngValue {
+    /// A generator of this algorithm, the same `seed` always generates the same numbers
+    ///
+    /// The default xoroshiro128+ uses the `seed` as is. The seeds of the other generators are
+    /// longer, so they are filled with the output of a xoroshiro128+ seeded with `seed`.
+    #[must_use]
+    pub fn to_rng(self, seed: [u8; 16]) -> NoiseRng {
+        let mut rng = Xoroshiro128Plus::from_seed(seed);
+        match self {
+            Self::Xoroshiro128Plus => NoiseRng::Xoroshiro128Plus(rng),
+            Self::Xoshiro256PlusPlus => {
+                NoiseRng::Xoshiro256PlusPlus(Xoshiro256PlusPlus::from_rng(&mut rng))
+            }
+            Self::ChaCha8 => NoiseRng::ChaCha8(ChaCha8Rng::from_rng(&mut rng)),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use noisy_silence::NoiseValue;
+
+use crate::{Error, Event};
+
+/// Spawn a thread that switches to the next noise type of `noises` every `interval`
+///
+/// The rotation continues after `current`, or starts at the first noise type if `current` is not
+/// in the list.
+pub(crate) fn spawn(
+    noises: Vec<NoiseValue>,
+    current: NoiseValue,
+    interval: Duration,
+    tx: mpsc::SyncSender<Event>,
+) -> Result<(), Error> {
+    if interval.is_zero() {
+        return Err(Error::RotateInterval);
+    }
+    let mut index = noises
+        .iter()
+        .position(|&noise| noise == current)
+        .unwrap_or(noises.len() - 1);
+    let _: thread::JoinHandle<()> = thread::Builder::new()
+        .name("rotate".into())
+        .spawn(move || {
+            loop {
+                thread::sleep(interval);
+                index = (index + 1) % noises.len();
+                if tx.send(Event::Noise(noises[index])).is_err() {
+                    break;
+                }
+            }
+        })
+        .map_err(Error::Thread)?;
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use std::sync::mpsc;
+
+use crate::{CtrlcMode, Error, Event, cancel};
+
+/// Handle ctrl+C and SIGTERM with [`cancel()`], and report SIGHUP as [`Event::Reload`] if `reload`
+///
+/// Without `reload`, SIGHUP keeps its default action and terminates the process, e.g. when the
+/// terminal is closed.
+#[cfg(unix)]
+pub(crate) fn install(
+    tx: mpsc::SyncSender<Event>,
+    mode: CtrlcMode,
+    reload: bool,
+) -> Result<(), Error> {
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+
+    let mut signals =
+        signal_hook::iterator::Signals::new([SIGINT, SIGTERM]).map_err(Error::CtrlC)?;
+    if reload {
+        signals.add_signal(SIGHUP).map_err(Error::CtrlC)?;
+    }
+    let _: std::thread::JoinHandle<()> = std::thread::Builder::new()
+        .name("signals".into())
+        .spawn(move || {
+            for signal in signals.forever() {
+                if signal == SIGHUP {
+                    // Never block, in case the main thread is busy with the last reload.
+                    let _: Result<(), mpsc::TrySendError<Event>> = tx.try_send(Event::Reload);
+                } else {
+                    cancel(&tx, mode);
+                }
+            }
+        })
+        .map_err(Error::Thread)?;
+    Ok(())
+}
+
+/// Handle ctrl+C with [`cancel()`]
+///
+/// With the "termination" feature, `ctrlc` traps all console control events, including closing
+/// the console window.
+#[cfg(not(unix))]
+pub(crate) fn install(
+    tx: mpsc::SyncSender<Event>,
+    mode: CtrlcMode,
+    _reload: bool,
+) -> Result<(), Error> {
+    ctrlc::try_set_handler(move || cancel(&tx, mode))?;
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use std::f64::consts::PI;
+use std::time::Duration;
+
+use rodio::source::{SeekError, noise};
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+use crate::{Error, NoiseRng, RngValue};
+
+/// Noise with a power spectral density proportional to 1/f<sup>α</sup>
+///
+/// White noise (α = 0) is shaped by a cascade of first-order pole-zero filters, with one pole per
+/// octave and a zero between each pair of poles, cf. "Generation of 1/f<sup>α</sup> noise" by
+/// Corsini and Saletti. Placing the zero at α/2 of the distance to the next pole makes the average
+/// slope of the cascade -10·α dB per decade. Below 5 Hz the spectrum is flat, so the noise never
+/// drifts away. The output has the same RMS level as the white noise it was generated from.
+#[derive(Debug)]
+pub struct SlopeNoise {
+    white: noise::WhiteUniform<NoiseRng>,
+    sections: Vec<Section>,
+    gain: f32,
+}
+
+impl SlopeNoise {
+    /// Generate noise with a slope of `alpha`: 0 is white, 1 is pink, and 2 is brownian noise
+    ///
+    /// # Errors
+    ///
+    /// Fails if `alpha` is not between 0 and 2.
+    pub fn new(sample_rate: SampleRate, alpha: f32, seed: [u8; 16]) -> Result<Self, Error> {
+        Self::with_rng(sample_rate, alpha, RngValue::default().to_rng(seed))
+    }
+
+    /// Generate noise with a slope of `alpha`, drawing the random numbers from `rng`
+    ///
+    /// # Errors
+    ///
+    /// Fails if `alpha` is not between 0 and 2.
+    pub fn with_rng(sample_rate: SampleRate, alpha: f32, rng: NoiseRng) -> Result<Self, Error> {
+        if !(0.0..=2.0).contains(&alpha) {
+            return Err(Error::Slope(alpha));
+        }
+        let nyquist = f64::from(sample_rate) / 2.0;
+        let sections: Vec<Section> = (0..32)
+            .map(|octave| LOWEST_POLE * 2f64.powi(octave))
+            .take_while(|&pole| pole < nyquist)
+            .map(|pole| {
+                let zero = (pole * 2f64.powf(f64::from(alpha) / 2.0)).min(nyquist * 0.99);
+                Section::new(sample_rate, pole, zero)
+            })
+            .collect();
+        #[expect(clippy::cast_possible_truncation)]
+        let gain = (1.0 / rms_gain(&sections)) as f32;
+        Ok(Self {
+            white: noise::WhiteUniform::new_with_rng(sample_rate, rng),
+            sections,
+            gain,
+        })
+    }
+}
+
+impl Iterator for SlopeNoise {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.white.next()?;
+        let sample = self
+            .sections
+            .iter_mut()
+            .fold(sample, |sample, section| section.process(sample));
+        Some(sample * self.gain)
+    }
+}
+
+impl Source for SlopeNoise {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.white.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Ok(())
+    }
+}
+
+/// A first-order pole-zero filter, the bilinear transform of `(1 + s/ω_zero) / (1 + s/ω_pole)`
+#[derive(Debug, Clone, Copy)]
+struct Section {
+    b0: f32,
+    b1: f32,
+    a1: f32,
+    z1: f32,
+}
+
+impl Section {
+    fn new(sample_rate: SampleRate, pole: f64, zero: f64) -> Self {
+        let warp = |frequency: f64| 1.0 / (PI * frequency / f64::from(sample_rate)).tan();
+        let (zero, pole) = (warp(zero), warp(pole));
+        let a0 = 1.0 + pole;
+        #[expect(clippy::cast_possible_truncation)]
+        Self {
+            b0: ((1.0 + zero) / a0) as f32,
+            b1: ((1.0 - zero) / a0) as f32,
+            a1: ((1.0 - pole) / a0) as f32,
+            z1: 0.0,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y;
+        y
+    }
+
+    /// The squared magnitude of the response at the normalized angular frequency `omega`
+    fn power(&self, omega: f64) -> f64 {
+        let (sin, cos) = omega.sin_cos();
+        let (b0, b1, a1) = (f64::from(self.b0), f64::from(self.b1), f64::from(self.a1));
+        let num = (b0 + b1 * cos).powi(2) + (b1 * sin).powi(2);
+        let den = (1.0 + a1 * cos).powi(2) + (a1 * sin).powi(2);
+        num / den
+    }
+}
+
+/// The factor by which the cascade changes the RMS level of white noise
+fn rms_gain(sections: &[Section]) -> f64 {
+    const STEPS: u32 = 1 << 16;
+    let total: f64 = (0..STEPS)
+        .map(|step| {
+            let omega = PI * (f64::from(step) + 0.5) / f64::from(STEPS);
+            sections.iter().map(|s| s.power(omega)).product::<f64>()
+        })
+        .sum();
+    (total / f64::from(STEPS)).sqrt()
+}
+
+/// The frequency in Hz of the lowest pole; the spectrum is flat below it
+const LOWEST_POLE: f64 = 5.0;
@@ -0,0 +1,212 @@
+use std::f64::consts::TAU;
+
+use rodio::{Sample, SampleRate};
+
+/// The power of a noise in octave bands, measured with an FFT
+#[derive(Debug, Clone)]
+pub(crate) struct Spectrum {
+    /// The center frequency in Hz, and the power of the band in dBFS
+    pub(crate) bands: Vec<(f64, f64)>,
+    /// The slope of the power spectral density in dB per octave, fitted over all bands
+    pub(crate) slope: f64,
+}
+
+impl Spectrum {
+    /// Average the power spectra of Hann-windowed segments of `samples`
+    ///
+    /// Only the octave bands below the Nyquist frequency are measured.
+    pub(crate) fn measure(
+        samples: impl IntoIterator<Item = Sample>,
+        sample_rate: SampleRate,
+    ) -> Self {
+        let window: Vec<f64> = (0..SEGMENT_LEN)
+            .map(|index| {
+                #[expect(clippy::cast_precision_loss)]
+                let x = index as f64 / SEGMENT_LEN as f64;
+                0.5 - 0.5 * (TAU * x).cos()
+            })
+            .collect();
+        let window_power: f64 = window.iter().map(|w| w * w).sum();
+
+        let mut power = vec![0.0; SEGMENT_LEN / 2 + 1];
+        let mut samples = samples.into_iter();
+        for _ in 0..SEGMENTS {
+            let mut re: Vec<f64> = samples
+                .by_ref()
+                .take(SEGMENT_LEN)
+                .zip(&window)
+                .map(|(sample, w)| f64::from(sample) * w)
+                .collect();
+            re.resize(SEGMENT_LEN, 0.0);
+            let mut im = vec![0.0; SEGMENT_LEN];
+            fft(&mut re, &mut im);
+            for (bin, power) in power.iter_mut().enumerate() {
+                *power += re[bin] * re[bin] + im[bin] * im[bin];
+            }
+        }
+        // Scale to a one-sided spectrum, whose bins add up to the mean square of the samples.
+        #[expect(clippy::cast_precision_loss)]
+        let scale = 2.0 / (window_power * SEGMENT_LEN as f64 * SEGMENTS as f64);
+        for power in &mut power {
+            *power *= scale;
+        }
+
+        #[expect(clippy::cast_precision_loss)]
+        let bin_width = f64::from(sample_rate) / SEGMENT_LEN as f64;
+        let nyquist = f64::from(sample_rate) / 2.0;
+        let mut bands = Vec::new();
+        let mut densities = Vec::new();
+        for center in OCTAVES {
+            let (low, high) = (center / 2f64.sqrt(), center * 2f64.sqrt());
+            if high > nyquist {
+                break;
+            }
+            let band_power: f64 = power
+                .iter()
+                .enumerate()
+                .filter(|&(bin, _)| {
+                    #[expect(clippy::cast_precision_loss)]
+                    let frequency = bin as f64 * bin_width;
+                    (low..high).contains(&frequency)
+                })
+                .map(|(_, power)| power)
+                .sum();
+            bands.push((center, power_db(band_power)));
+            densities.push((center.log2(), power_db(band_power / (high - low))));
+        }
+        Self {
+            bands,
+            slope: fit_slope(&densities),
+        }
+    }
+
+    /// The exponent α of a 1/f<sup>α</sup> power spectral density with the measured slope
+    pub(crate) fn alpha(&self) -> f64 {
+        -self.slope / (10.0 * 2f64.log10())
+    }
+}
+
+/// The slope of the least-squares line through the points
+fn fit_slope(points: &[(f64, f64)]) -> f64 {
+    #[expect(clippy::cast_precision_loss)]
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|&(x, _)| x).sum::<f64>() / count;
+    let mean_y = points.iter().map(|&(_, y)| y).sum::<f64>() / count;
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for &(x, y) in points {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance += (x - mean_x) * (x - mean_x);
+    }
+    if variance > 0.0 {
+        covariance / variance
+    } else {
+        0.0
+    }
+}
+
+fn power_db(power: f64) -> f64 {
+    10.0 * power.max(1e-20).log10()
+}
+
+/// An in-place radix-2 FFT, the length of `re` and `im` must be a power of two
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let len = re.len();
+    let mut j = 0;
+    for i in 1..len {
+        let mut bit = len >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut size = 2;
+    while size <= len {
+        #[expect(clippy::cast_precision_loss)]
+        let angle = -TAU / size as f64;
+        for start in (0..len).step_by(size) {
+            for k in 0..size / 2 {
+                #[expect(clippy::cast_precision_loss)]
+                let (sin, cos) = (angle * k as f64).sin_cos();
+                let (a, b) = (start + k, start + k + size / 2);
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        size *= 2;
+    }
+}
+
+/// The length of the analysed segments, about 0.17 s at 48 kHz
+const SEGMENT_LEN: usize = 8192;
+
+/// The number of averaged segments, about 11 s at 48 kHz
+const SEGMENTS: usize = 64;
+
+/// The center frequencies of the octave bands in Hz
+const OCTAVES: [f64; 10] = [
+    31.25, 62.5, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+#[cfg(test)]
+mod tests {
+    use noisy_silence::{GreyNoise, NoiseValue, RngValue, SlopeNoise, VossNoise};
+
+    use super::*;
+
+    #[test]
+    fn slope_noise_has_requested_tilt() {
+        for alpha in [0.0, 0.5, 1.0, 1.5, 2.0] {
+            let spectrum =
+                Spectrum::measure(SlopeNoise::new(48_000, alpha, [0; 16]).unwrap(), 48_000);
+            let actual = spectrum.alpha();
+            assert!(
+                (actual - f64::from(alpha)).abs() < 0.15,
+                "alpha {alpha}: measured {actual:.2}, {:.2} dB per octave",
+                spectrum.slope,
+            );
+        }
+    }
+
+    #[test]
+    fn grey_noise_follows_inverse_loudness_contour() {
+        let grey = GreyNoise::new_with_rng(48_000, RngValue::default().to_rng([0; 16]));
+        let spectrum = Spectrum::measure(grey, 48_000);
+        // The bands get wider by 3 dB per octave.
+        let level = |center: f64| {
+            let (_, power) = spectrum.bands.iter().find(|&&(c, _)| c == center).unwrap();
+            power - 10.0 * center.log10()
+        };
+        let density = |center| level(center) - level(1000.0);
+        assert!(density(62.5) > 15.0, "bass {:.1} dB", density(62.5));
+        for pair in OCTAVES[1..=7].windows(2) {
+            assert!(density(pair[0]) > density(pair[1]), "{pair:?}");
+        }
+        assert!(density(4000.0) < -2.0, "dip {:.1} dB", density(4000.0));
+        assert!(density(16000.0) > 5.0, "treble {:.1} dB", density(16000.0));
+    }
+
+    #[test]
+    fn both_pink_algorithms_fall_by_3_db_per_octave() {
+        let voss = VossNoise::new_with_rng(48_000, RngValue::default().to_rng([0; 16]));
+        let rodio = NoiseValue::Pink.to_noise(48_000, [0; 16]);
+        for (name, spectrum) in [
+            ("voss", Spectrum::measure(voss, 48_000)),
+            ("rodio", Spectrum::measure(rodio, 48_000)),
+        ] {
+            let slope = spectrum.slope;
+            assert!(
+                (slope + 3.0).abs() < 0.5,
+                "{name}: {slope:.2} dB per octave"
+            );
+        }
+    }
+}
@@ -0,0 +1,444 @@
+use std::f32::consts::{FRAC_PI_4, TAU};
+use std::sync::Arc;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::Relaxed;
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+use tracing::warn;
+
+use crate::Error;
+
+/// Interleaves mono sources into one source with one channel per input
+pub(crate) struct Interleave<S> {
+    inputs: Vec<S>,
+    index: usize,
+}
+
+impl<S: Source> Interleave<S> {
+    pub(crate) fn new(inputs: Vec<S>) -> Self {
+        debug_assert!(!inputs.is_empty() && inputs.iter().all(|input| input.channels() == 1));
+        Self { inputs, index: 0 }
+    }
+}
+
+impl<S: Source> Iterator for Interleave<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.inputs.get_mut(self.index)?.next();
+        self.index += 1;
+        if self.index == self.inputs.len() {
+            self.index = 0;
+        }
+        sample
+    }
+}
+
+impl<S: Source> Source for Interleave<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    #[expect(clippy::cast_possible_truncation)]
+    fn channels(&self) -> ChannelCount {
+        self.inputs.len() as ChannelCount
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.inputs[0].sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inputs[0].total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.index = 0;
+        self.inputs
+            .iter_mut()
+            .try_for_each(|input| input.try_seek(pos))
+    }
+}
+
+/// Plays the same mono source on multiple channels
+pub(crate) struct Replicate<S> {
+    input: S,
+    channels: ChannelCount,
+    index: ChannelCount,
+    sample: Sample,
+}
+
+impl<S: Source> Replicate<S> {
+    pub(crate) fn new(input: S, channels: ChannelCount) -> Self {
+        debug_assert!(input.channels() == 1 && channels > 0);
+        Self {
+            input,
+            channels,
+            index: 0,
+            sample: 0.0,
+        }
+    }
+}
+
+impl<S: Source> Iterator for Replicate<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == 0 {
+            self.sample = self.input.next()?;
+        }
+        self.index += 1;
+        if self.index == self.channels {
+            self.index = 0;
+        }
+        Some(self.sample)
+    }
+}
+
+impl<S: Source> Source for Replicate<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.index = 0;
+        self.input.try_seek(pos)
+    }
+}
+
+/// The highest supported `--auto-pan-rate` in Hz, the noise should drift, not swing
+pub(crate) const MAX_AUTO_PAN_RATE: f32 = 0.2;
+
+/// Weights the left and the right channel of a stereo source with constant power
+pub(crate) struct Pan<S> {
+    input: S,
+    position: Position,
+    gains: [f32; 2],
+    channel: usize,
+}
+
+/// Where a [`Pan`] takes its position between -1 and 1 from
+enum Position {
+    /// The bits of an `f32`, which can change while playing, and the last bits that were read
+    Shared(Arc<AtomicU32>, u32),
+    /// A slow sine wave
+    Sweep {
+        /// The phase increment per frame in radians
+        step: f32,
+        phase: f32,
+    },
+}
+
+impl<S: Source> Pan<S> {
+    pub(crate) fn new(input: S, position: Arc<AtomicU32>) -> Self {
+        let bits = position.load(Relaxed);
+        Self {
+            input,
+            position: Position::Shared(position, bits),
+            gains: pan_gains(f32::from_bits(bits)),
+            channel: 0,
+        }
+    }
+
+    /// Sweep the source from the center to the right, to the left, and back, `rate` times per
+    /// second
+    pub(crate) fn sweep(input: S, rate: f32) -> Self {
+        #[expect(clippy::cast_precision_loss)]
+        let step = TAU * rate / input.sample_rate() as f32;
+        Self {
+            input,
+            position: Position::Sweep { step, phase: 0.0 },
+            gains: pan_gains(0.0),
+            channel: 0,
+        }
+    }
+
+    /// Read the position again at the start of each frame
+    #[inline]
+    fn update(&mut self) {
+        match &mut self.position {
+            Position::Shared(position, bits) => {
+                let new_bits = position.load(Relaxed);
+                if new_bits != *bits {
+                    *bits = new_bits;
+                    self.gains = pan_gains(f32::from_bits(new_bits));
+                }
+            }
+            Position::Sweep { step, phase } => {
+                *phase = (*phase + *step) % TAU;
+                self.gains = pan_gains(phase.sin());
+            }
+        }
+    }
+}
+
+/// The gains of the left and the right channel
+fn pan_gains(pan: f32) -> [f32; 2] {
+    let (right, left) = ((pan + 1.0) * FRAC_PI_4).sin_cos();
+    [left, right]
+}
+
+impl<S: Source> Iterator for Pan<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.channel == 0 {
+            self.update();
+        }
+        let sample = self.input.next()? * self.gains[self.channel];
+        self.channel ^= 1;
+        Some(sample)
+    }
+}
+
+impl<S: Source> Source for Pan<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.channel = 0;
+        if let Position::Sweep { step, phase } = &mut self.position {
+            #[expect(clippy::cast_precision_loss)]
+            let frames = pos.as_secs_f32() * self.input.sample_rate() as f32;
+            *phase = (*step * frames) % TAU;
+            self.gains = pan_gains(phase.sin());
+        }
+        self.input.try_seek(pos)
+    }
+}
+
+/// The longest supported `--haas` delay in milliseconds, any longer and it would sound like an echo
+const MAX_HAAS_DELAY: f32 = 40.0;
+
+/// Delays the right channel of a stereo source
+pub(crate) struct Haas<S> {
+    input: S,
+    /// The delayed samples of the right channel, a ring buffer
+    buffer: Vec<Sample>,
+    position: usize,
+    channel: usize,
+}
+
+impl<S: Source> Haas<S> {
+    pub(crate) fn new(input: S, delay: f32) -> Result<Self, Error> {
+        if !(delay > 0.0 && delay <= MAX_HAAS_DELAY) {
+            return Err(Error::Haas(delay));
+        }
+        if input.channels() != 2 {
+            return Err(Error::HaasChannels(input.channels()));
+        }
+        #[expect(
+            clippy::cast_possible_truncation,
+            clippy::cast_precision_loss,
+            clippy::cast_sign_loss
+        )]
+        let frames = (delay * input.sample_rate() as f32 / 1000.0).round() as usize;
+        Ok(Self {
+            input,
+            buffer: vec![0.0; frames.max(1)],
+            position: 0,
+            channel: 0,
+        })
+    }
+}
+
+impl<S: Source> Iterator for Haas<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut sample = self.input.next()?;
+        if self.channel == 1 {
+            sample = std::mem::replace(&mut self.buffer[self.position], sample);
+            self.position += 1;
+            if self.position == self.buffer.len() {
+                self.position = 0;
+            }
+        }
+        self.channel ^= 1;
+        Some(sample)
+    }
+}
+
+impl<S: Source> Source for Haas<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.channel = 0;
+        self.position = 0;
+        self.buffer.fill(0.0);
+        self.input.try_seek(pos)
+    }
+}
+
+/// The widest supported `--width`
+const MAX_WIDTH: f32 = 2.0;
+
+/// Scales the difference between the channels of a stereo source with mid/side processing
+pub(crate) struct Width<S> {
+    input: S,
+    width: f32,
+    /// The right sample of the current frame
+    right: Option<Sample>,
+}
+
+impl<S: Source> Width<S> {
+    /// A `width` outside of 0 to 2 is clamped
+    pub(crate) fn new(input: S, width: f32) -> Result<Self, Error> {
+        if input.channels() != 2 {
+            return Err(Error::WidthChannels(input.channels()));
+        }
+        let clamped = width.clamp(0.0, MAX_WIDTH);
+        #[expect(clippy::float_cmp)]
+        if clamped != width {
+            warn!("The stereo width {width:?} is not between 0 and 2, using {clamped:?}.");
+        }
+        Ok(Self {
+            input,
+            width: clamped,
+            right: None,
+        })
+    }
+}
+
+impl<S: Source> Iterator for Width<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(right) = self.right.take() {
+            return Some(right);
+        }
+        let left = self.input.next()?;
+        let right = self.input.next()?;
+        let mid = (left + right) * 0.5;
+        let side = (left - right) * 0.5 * self.width;
+        self.right = Some(mid - side);
+        Some(mid + side)
+    }
+}
+
+impl<S: Source> Source for Width<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.right = None;
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    use super::*;
+
+    #[test]
+    fn pan_keeps_constant_power() {
+        for (pan, expected) in [
+            (-1.0, [1.0, 0.0]),
+            (0.0, [FRAC_1_SQRT_2, FRAC_1_SQRT_2]),
+            (1.0, [0.0, 1.0]),
+        ] {
+            let gains = pan_gains(pan);
+            for (gain, expected) in gains.into_iter().zip(expected) {
+                assert!((gain - expected).abs() < 1e-6, "{pan}: {gains:?}");
+            }
+            let power: f32 = gains.iter().map(|gain| gain * gain).sum();
+            assert!((power - 1.0).abs() < 1e-6, "{pan}: {power}");
+        }
+    }
+
+    #[test]
+    fn zero_width_is_mono() {
+        let samples: Vec<Sample> = (0..64).map(|index| (index as f32 * 0.7).sin()).collect();
+        let source = rodio::buffer::SamplesBuffer::new(2, 48_000, samples);
+        let output: Vec<Sample> = Width::new(source, 0.0).unwrap().collect();
+        for frame in output.chunks(2) {
+            assert_eq!(frame[0], frame[1]);
+        }
+    }
+}
@@ -0,0 +1,19 @@
+/// Tell systemd that the noise is playing
+pub(crate) fn ready() {
+    #[cfg(unix)]
+    notify(sd_notify::NotifyState::Ready);
+}
+
+/// Tell systemd that the process is shutting down
+pub(crate) fn stopping() {
+    #[cfg(unix)]
+    notify(sd_notify::NotifyState::Stopping);
+}
+
+/// Notify systemd if the process runs as a service with `Type=notify`, i.e. if `NOTIFY_SOCKET` is set
+#[cfg(unix)]
+fn notify(state: sd_notify::NotifyState<'_>) {
+    if let Err(err) = sd_notify::notify(&[state]) {
+        tracing::warn!("Could not notify systemd: {err}");
+    }
+}
@@ -0,0 +1,276 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+use tracing::{info, warn};
+
+use crate::quantize::{Quantizer, SampleFormat};
+use crate::{Error, wav_spec};
+
+/// A block of samples that a [`Tee`] copied, or the request to finish the file
+pub(crate) enum Message {
+    Block {
+        channels: ChannelCount,
+        sample_rate: SampleRate,
+        samples: Vec<Sample>,
+        /// Where the emptied buffer goes back to, so the audio thread never allocates
+        recycle: mpsc::SyncSender<Vec<Sample>>,
+    },
+    Finish,
+}
+
+/// Writes the samples that [`Tee`]s copied into a WAV file in a background thread
+pub(crate) struct Recorder {
+    recording: Recording,
+    thread: thread::JoinHandle<Result<(), Error>>,
+}
+
+/// The connection of a [`Tee`] to the [`Recorder`]
+#[derive(Debug, Clone)]
+pub(crate) struct Recording {
+    tx: mpsc::SyncSender<Message>,
+    /// The blocks that were not recorded, because the recorder fell behind
+    dropped: Arc<AtomicU64>,
+}
+
+impl Recorder {
+    /// Create the file `path`, so it fails early if it cannot be written, and start the thread
+    pub(crate) fn spawn(path: &Path, quantizer: Quantizer) -> Result<Self, Error> {
+        let file = File::create(path).map_err(|err| Error::Tee(path.to_owned(), err))?;
+        let (tx, rx) = mpsc::sync_channel(QUEUE_LENGTH);
+        let path = path.to_owned();
+        let thread = thread::Builder::new()
+            .name("tee".into())
+            .spawn(move || record(&path, BufWriter::new(file), quantizer, &rx))
+            .map_err(Error::Thread)?;
+        let recording = Recording {
+            tx,
+            dropped: Arc::default(),
+        };
+        Ok(Self { recording, thread })
+    }
+
+    /// The connection to pass to [`Tee::new()`]
+    pub(crate) fn sender(&self) -> Recording {
+        self.recording.clone()
+    }
+
+    /// Write the remaining samples, and finalize the file
+    ///
+    /// Drop the output stream first, so the last samples of its [`Tee`] are not lost.
+    pub(crate) fn finish(self) -> Result<(), Error> {
+        let _: Result<(), mpsc::SendError<Message>> = self.recording.tx.send(Message::Finish);
+        self.thread
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))?;
+        let dropped = self.recording.dropped.load(Relaxed);
+        if dropped > 0 {
+            warn!(
+                "The recording fell behind the playback, {dropped} blocks of {BLOCK_SIZE} samples \
+                 are missing.",
+            );
+        }
+        Ok(())
+    }
+}
+
+fn record(
+    path: &Path,
+    file: BufWriter<File>,
+    mut quantizer: Quantizer,
+    rx: &mpsc::Receiver<Message>,
+) -> Result<(), Error> {
+    let Ok(Message::Block {
+        channels,
+        sample_rate,
+        mut samples,
+        mut recycle,
+    }) = rx.recv()
+    else {
+        return Ok(());
+    };
+    let spec = wav_spec(channels, sample_rate, quantizer.format());
+    let mut writer = hound::WavWriter::new(file, spec)?;
+    loop {
+        for &sample in &samples {
+            match quantizer.format() {
+                SampleFormat::F32 => writer.write_sample(sample)?,
+                _ => writer.write_sample(quantizer.quantize(sample))?,
+            }
+        }
+        samples.clear();
+        let _: Result<(), mpsc::TrySendError<Vec<Sample>>> = recycle.try_send(samples);
+        (samples, recycle) = match rx.recv() {
+            Ok(Message::Block {
+                channels: c,
+                sample_rate: r,
+                samples,
+                recycle,
+            }) if (c, r) == (channels, sample_rate) => (samples, recycle),
+            Ok(Message::Block { .. }) => {
+                warn!("The format of the stream changed, stopped recording.");
+                break;
+            }
+            Ok(Message::Finish) | Err(mpsc::RecvError) => break,
+        };
+    }
+    writer.finalize()?;
+    info!("Done writing {}.", path.display());
+    Ok(())
+}
+
+/// Copies the samples of a source to a [`Recorder`]
+///
+/// The audio thread never waits for the recorder, and never allocates: the buffers are allocated
+/// up front, and the recorder sends them back. If it falls behind, the samples are not recorded,
+/// and the recorder warns about the gaps when it finishes.
+pub(crate) struct Tee<S> {
+    input: S,
+    channels: ChannelCount,
+    sample_rate: SampleRate,
+    buffer: Vec<Sample>,
+    recording: Recording,
+    /// The empty buffers, and the way back for them
+    spare: mpsc::Receiver<Vec<Sample>>,
+    recycle: mpsc::SyncSender<Vec<Sample>>,
+}
+
+impl<S: Source> Tee<S> {
+    pub(crate) fn new(input: S, recording: Recording) -> Self {
+        // One buffer for each place in the queue, one being filled, and one being written.
+        let (recycle, spare) = mpsc::sync_channel(QUEUE_LENGTH + 1);
+        for _ in 0..=QUEUE_LENGTH {
+            let _: Result<(), mpsc::TrySendError<Vec<Sample>>> =
+                recycle.try_send(Vec::with_capacity(BLOCK_SIZE));
+        }
+        Self {
+            channels: input.channels(),
+            sample_rate: input.sample_rate(),
+            input,
+            buffer: Vec::with_capacity(BLOCK_SIZE),
+            recording,
+            spare,
+            recycle,
+        }
+    }
+}
+
+impl<S> Tee<S> {
+    /// Pass the buffered samples to the recorder
+    fn flush(&mut self) {
+        let Ok(next) = self.spare.try_recv() else {
+            // Every buffer is still waiting for the recorder.
+            self.drop_block();
+            return;
+        };
+        let samples = std::mem::replace(&mut self.buffer, next);
+        let message = Message::Block {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            samples,
+            recycle: self.recycle.clone(),
+        };
+        let (full, message) = match self.recording.tx.try_send(message) {
+            Ok(()) => return,
+            Err(mpsc::TrySendError::Full(message)) => (true, message),
+            // The recorder stopped, and said why.
+            Err(mpsc::TrySendError::Disconnected(message)) => (false, message),
+        };
+        if let Message::Block { samples, .. } = message {
+            let next = std::mem::replace(&mut self.buffer, samples);
+            let _: Result<(), mpsc::TrySendError<Vec<Sample>>> = self.recycle.try_send(next);
+        }
+        if full {
+            self.drop_block();
+        } else {
+            self.buffer.clear();
+        }
+    }
+
+    fn drop_block(&mut self) {
+        self.buffer.clear();
+        let _: u64 = self.recording.dropped.fetch_add(1, Relaxed);
+    }
+}
+
+impl<S> Drop for Tee<S> {
+    fn drop(&mut self) {
+        if !self.buffer.is_empty() {
+            self.flush();
+        }
+    }
+}
+
+impl<S: Source> Iterator for Tee<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        self.buffer.push(sample);
+        if self.buffer.len() == BLOCK_SIZE {
+            self.flush();
+        }
+        Some(sample)
+    }
+}
+
+impl<S: Source> Source for Tee<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+/// The number of samples that a [`Tee`] passes to the recorder at once
+const BLOCK_SIZE: usize = 4096;
+
+/// The number of blocks that may wait for the recorder, about 3 s of 48 kHz stereo
+const QUEUE_LENGTH: usize = 64;
+
+#[cfg(test)]
+mod tests {
+    use rodio::buffer::SamplesBuffer;
+
+    use super::*;
+
+    #[test]
+    fn counts_dropped_blocks() {
+        let (tx, rx) = mpsc::sync_channel(2);
+        let recording = Recording {
+            tx,
+            dropped: Arc::default(),
+        };
+        let input = SamplesBuffer::new(1, 48_000, vec![0.0; 5 * BLOCK_SIZE]);
+        let _: Vec<Sample> = Tee::new(input, recording.clone()).collect();
+        assert_eq!(rx.try_iter().count(), 2);
+        assert_eq!(recording.dropped.load(Relaxed), 3);
+    }
+}
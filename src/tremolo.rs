@@ -0,0 +1,89 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+/// The highest supported LFO rate in Hz, any faster and the tremolo would become audible as a tone
+pub(crate) const MAX_LFO_RATE: f32 = 5.0;
+
+/// Slowly modulates the amplitude of a source with a sine wave
+pub(crate) struct Tremolo<S> {
+    input: S,
+    depth: f32,
+    /// The phase increment per frame in radians
+    step: f32,
+    phase: f32,
+    gain: f32,
+    channel: ChannelCount,
+}
+
+impl<S: Source> Tremolo<S> {
+    pub(crate) fn new(input: S, rate: f32, depth: f32) -> Self {
+        #[expect(clippy::cast_precision_loss)]
+        let step = TAU * rate / input.sample_rate() as f32;
+        let mut this = Self {
+            input,
+            depth,
+            step,
+            phase: 0.0,
+            gain: 0.0,
+            channel: 0,
+        };
+        this.gain = this.gain();
+        this
+    }
+
+    #[inline]
+    fn gain(&self) -> f32 {
+        1.0 - self.depth + self.depth * (0.5 + 0.5 * self.phase.sin())
+    }
+}
+
+impl<S: Source> Iterator for Tremolo<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()? * self.gain;
+        self.channel += 1;
+        if self.channel == self.input.channels() {
+            self.channel = 0;
+            self.phase = (self.phase + self.step) % TAU;
+            self.gain = self.gain();
+        }
+        Some(sample)
+    }
+}
+
+impl<S: Source> Source for Tremolo<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.channel = 0;
+        #[expect(clippy::cast_precision_loss)]
+        let frames = pos.as_secs_f32() * self.input.sample_rate() as f32;
+        self.phase = (self.step * frames) % TAU;
+        self.gain = self.gain();
+        self.input.try_seek(pos)
+    }
+}
@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use rand::Rng;
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+use crate::Error;
+
+/// Velvet noise, sparse random impulses with a configurable density
+///
+/// Every grid cell of `sample_rate / density` samples contains one impulse of a random polarity
+/// at a random position. With the default density it generates the same samples as
+/// [`rodio::source::noise::Velvet`], which does not let you choose the density of seeded noise.
+#[derive(Debug, Clone)]
+pub struct VelvetNoise<R: Rng> {
+    sample_rate: SampleRate,
+    rng: R,
+    /// The length of a grid cell in samples
+    grid_size: f32,
+    grid_pos: f32,
+    impulse_pos: f32,
+}
+
+impl<R: Rng> VelvetNoise<R> {
+    /// Generate velvet noise with 2000 impulses per second
+    pub fn new_with_rng(sample_rate: SampleRate, rng: R) -> Self {
+        Self::new_unchecked(sample_rate, DEFAULT_DENSITY, rng)
+    }
+
+    /// Generate velvet noise with `density` impulses per second
+    ///
+    /// # Errors
+    ///
+    /// Fails if `density` is not above zero, or exceeds the sample rate.
+    pub fn with_density(sample_rate: SampleRate, density: f32, rng: R) -> Result<Self, Error> {
+        #[expect(clippy::cast_precision_loss)]
+        if density > 0.0 && density <= sample_rate as f32 {
+            Ok(Self::new_unchecked(sample_rate, density, rng))
+        } else {
+            Err(Error::VelvetDensity(density, sample_rate))
+        }
+    }
+
+    fn new_unchecked(sample_rate: SampleRate, density: f32, mut rng: R) -> Self {
+        #[expect(clippy::cast_precision_loss)]
+        let grid_size = sample_rate as f32 / density;
+        let impulse_pos = rng.random::<f32>() * grid_size;
+        Self {
+            sample_rate,
+            rng,
+            grid_size,
+            grid_pos: 0.0,
+            impulse_pos,
+        }
+    }
+}
+
+impl<R: Rng> Iterator for VelvetNoise<R> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let output = if self.grid_pos as usize == self.impulse_pos as usize {
+            if self.rng.random::<bool>() { 1.0 } else { -1.0 }
+        } else {
+            0.0
+        };
+        self.grid_pos += 1.0;
+        if self.grid_pos >= self.grid_size {
+            self.grid_pos = 0.0;
+            self.impulse_pos = self.rng.random::<f32>() * self.grid_size;
+        }
+        Some(output)
+    }
+}
+
+impl<R: Rng> Source for VelvetNoise<R> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Ok(())
+    }
+}
+
+/// The density of rodio's velvet noise in impulses per second
+const DEFAULT_DENSITY: f32 = 2000.0;
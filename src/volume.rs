@@ -0,0 +1,262 @@
+use std::sync::Arc;
+use std::sync::atomic::Ordering::Relaxed;
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+use tracing::{info, warn};
+
+use crate::{Gain, metrics};
+
+/// Multiplies a source with a [`Gain`] that can be changed while playing
+///
+/// A changed gain is approached within about [`GAIN_SMOOTHING`], so the change does not click.
+/// If the `balance` is not empty, every channel is multiplied with its own factor, too.
+pub(crate) struct Volume<S> {
+    input: S,
+    gain: Arc<Gain>,
+    balance: Vec<f32>,
+    channels: usize,
+    channel: usize,
+    /// The gain that is applied to the current frame
+    current: f32,
+    /// The coefficient of the one-pole smoothing of the gain
+    smoothing: f32,
+}
+
+impl<S: Source> Volume<S> {
+    pub(crate) fn new(input: S, gain: Arc<Gain>, balance: Vec<f32>) -> Self {
+        debug_assert!(balance.is_empty() || balance.len() == usize::from(input.channels()));
+        #[expect(clippy::cast_precision_loss)]
+        let rate = input.sample_rate() as f32;
+        Self {
+            channels: usize::from(input.channels()),
+            current: gain.output(),
+            smoothing: 1.0 - (-1.0 / (GAIN_SMOOTHING * rate)).exp(),
+            input,
+            gain,
+            balance,
+            channel: 0,
+        }
+    }
+}
+
+impl<S: Source> Iterator for Volume<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        self.gain.beat();
+        // Every channel of a frame gets the same gain.
+        if self.channel == 0 {
+            self.current += self.smoothing * (self.gain.output() - self.current);
+        }
+        let balance = self.balance.get(self.channel).copied().unwrap_or(1.0);
+        self.channel = (self.channel + 1) % self.channels;
+        Some(sample * self.current * balance)
+    }
+}
+
+impl<S: Source> Source for Volume<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+/// The time constant in seconds in which a [`Volume`] follows a changed gain
+const GAIN_SMOOTHING: f32 = 0.02;
+
+/// Tracks the peak of a source, and counts the samples close to the full scale, so that
+/// [`report_headroom()`] can report them when the stream ends
+pub(crate) struct Headroom<S> {
+    input: S,
+    peak: f32,
+    counters: Arc<metrics::Counters>,
+}
+
+impl<S: Source> Headroom<S> {
+    pub(crate) fn new(input: S, counters: Arc<metrics::Counters>) -> Self {
+        Self {
+            input,
+            peak: 0.0,
+            counters,
+        }
+    }
+}
+
+/// Log the peak level and the samples close to the full scale that the [`Headroom`]s saw, after
+/// the stream has ended
+pub(crate) fn report_headroom(counters: &metrics::Counters) {
+    let peak = f32::from_bits(counters.peak.load(Relaxed));
+    if peak == 0.0 {
+        return;
+    }
+    info!(
+        "The peak level was {:.1} dBFS.",
+        noisy_silence::dbfs(f64::from(peak)),
+    );
+    let loud = counters.loud.load(Relaxed);
+    if loud > 0 {
+        warn!(
+            "{loud} samples exceeded {}% of the full scale, consider a lower amplitude.",
+            LOUD_THRESHOLD * 100.0,
+        );
+    }
+}
+
+impl<S: Source> Iterator for Headroom<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        let level = sample.abs();
+        if level > self.peak {
+            self.peak = level;
+            // The bits of non-negative floats are ordered like their values.
+            let _: u32 = self.counters.peak.fetch_max(level.to_bits(), Relaxed);
+        }
+        if level > LOUD_THRESHOLD {
+            let _: u64 = self.counters.loud.fetch_add(1, Relaxed);
+        }
+        Some(sample)
+    }
+}
+
+impl<S: Source> Source for Headroom<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+/// The level above which [`Headroom`] counts a sample as close to the full scale
+const LOUD_THRESHOLD: f32 = 0.99;
+
+/// Clamps the samples of a source to the full scale, and counts how many samples were limited
+pub(crate) struct Limiter<S> {
+    input: S,
+    counters: Arc<metrics::Counters>,
+}
+
+impl<S: Source> Limiter<S> {
+    pub(crate) fn new(input: S, counters: Arc<metrics::Counters>) -> Self {
+        Self { input, counters }
+    }
+}
+
+/// Warn about the samples that the [`Limiter`]s clamped, after the stream has ended
+pub(crate) fn warn_limited(counters: &metrics::Counters) {
+    let limited = counters.limited.load(Relaxed);
+    if limited > 0 {
+        warn!(
+            "Limited {limited} samples that exceeded the full scale, consider a lower amplitude."
+        );
+    }
+}
+
+impl<S: Source> Iterator for Limiter<S> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.input.next()?;
+        if (-1.0..=1.0).contains(&sample) {
+            return Some(sample);
+        }
+        let _: u64 = self.counters.limited.fetch_add(1, Relaxed);
+        Some(sample.clamp(-1.0, 1.0))
+    }
+}
+
+impl<S: Source> Source for Limiter<S> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.input.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volume_ramps_a_changed_gain() {
+        let gain = Arc::new(Gain::new(1.0));
+        let ones = rodio::buffer::SamplesBuffer::new(2, 48_000, vec![1.0; 48_000]);
+        let mut volume = Volume::new(ones, Arc::clone(&gain), vec![]);
+        assert_eq!(volume.next(), Some(1.0));
+        assert_eq!(volume.next(), Some(1.0));
+        gain.set(0.0);
+        let output: Vec<f32> = volume.by_ref().take(19_200).collect();
+        assert!(output[0] > 0.99);
+        for (frame, next) in output.chunks(2).zip(output.chunks(2).skip(1)) {
+            assert_eq!(frame[0], frame[1]);
+            assert!(next[0] < frame[0]);
+        }
+        // Ten time constants later the gain has arrived.
+        assert!(output[19_199] < 1e-4);
+    }
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use rand::Rng;
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+/// Pink noise generated with the Voss-McCartney algorithm
+///
+/// The output is the sum of a white noise sample and 16 held random values. Row `k` gets a
+/// new value every `2^(k + 1)` samples, and the updates are staggered, so that only one row changes
+/// per sample. This needs two random numbers per sample, while [`rodio::source::noise::Pink`]
+/// draws a new number for each of its generators whose period ended.
+#[derive(Debug, Clone)]
+pub struct VossNoise<R: Rng> {
+    sample_rate: SampleRate,
+    rng: R,
+    rows: [f32; ROWS],
+    counter: u32,
+}
+
+impl<R: Rng> VossNoise<R> {
+    /// Generate pink noise using the random number generator `rng`
+    pub fn new_with_rng(sample_rate: SampleRate, mut rng: R) -> Self {
+        let rows = std::array::from_fn(|_| random(&mut rng));
+        Self {
+            sample_rate,
+            rng,
+            rows,
+            counter: 0,
+        }
+    }
+}
+
+/// A uniformly distributed sample between -1 and 1
+#[inline]
+fn random(rng: &mut impl Rng) -> f32 {
+    rng.random::<f32>() * 2.0 - 1.0
+}
+
+impl<R: Rng> Iterator for VossNoise<R> {
+    type Item = Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.counter = self.counter.wrapping_add(1);
+        if let Some(row) = self.rows.get_mut(self.counter.trailing_zeros() as usize) {
+            *row = random(&mut self.rng);
+        }
+        let sum: f32 = self.rows.iter().sum();
+        #[expect(clippy::cast_precision_loss)]
+        Some((sum + random(&mut self.rng)) / (ROWS + 1) as f32)
+    }
+}
+
+impl<R: Rng> Source for VossNoise<R> {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Ok(())
+    }
+}
+
+/// The number of held random values, the slowest row changes every 1.4 s at 48 kHz
+const ROWS: usize = 16;
@@ -0,0 +1,39 @@
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::Duration;
+
+use crate::{Error, Event, Gain};
+
+/// Spawn a thread that reports [`Event::Stalled`] if no samples were played for `timeout`
+///
+/// It only reports a stalled stream once, until the samples start flowing again. The watch begins
+/// after the `delay`, while the stream is waiting for it, no samples are pulled through the gain.
+pub(crate) fn spawn(
+    gain: Arc<Gain>,
+    timeout: Duration,
+    delay: Duration,
+    tx: mpsc::SyncSender<Event>,
+) -> Result<(), Error> {
+    if timeout.is_zero() {
+        return Err(Error::WatchdogTimeout);
+    }
+    let _: thread::JoinHandle<()> = thread::Builder::new()
+        .name("watchdog".into())
+        .spawn(move || {
+            thread::sleep(delay);
+            let mut last = gain.heartbeat();
+            let mut armed = true;
+            loop {
+                thread::sleep(timeout);
+                let heartbeat = gain.heartbeat();
+                if heartbeat != last {
+                    last = heartbeat;
+                    armed = true;
+                } else if armed && tx.try_send(Event::Stalled).is_ok() {
+                    armed = false;
+                }
+            }
+        })
+        .map_err(Error::Thread)?;
+    Ok(())
+}